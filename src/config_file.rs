@@ -0,0 +1,233 @@
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use toml::{Table, Value};
+
+use crate::error::Error;
+use crate::subparsers;
+use crate::types::{AddedStr, ColourDepth, Config};
+
+fn config_home() -> Option<PathBuf> {
+    match env::var_os("XDG_CONFIG_HOME") {
+        Some(xdg_config_home) if !xdg_config_home.is_empty() => Some(PathBuf::from(xdg_config_home)),
+        _ => env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")),
+    }
+}
+
+/// `$XDG_CONFIG_HOME/rainbow_ls/config.toml`, falling back to `~/.config` when unset.
+pub fn default_path() -> Option<PathBuf> {
+    config_home().map(|config_home| config_home.join("rainbow_ls").join("config.toml"))
+}
+
+fn fail<T>(left: &str, message: &str) -> Result<T, Error> {
+    Err(Error::argument(left, message.to_string()))
+}
+
+fn as_os_string(left: &str, value: &Value) -> Result<OsString, Error> {
+    match value {
+        Value::String(string) => Ok(OsString::from(string)),
+        Value::Integer(integer) => Ok(OsString::from(integer.to_string())),
+        Value::Float(float) => Ok(OsString::from(float.to_string())),
+        _ => fail(left, "Expected a string or a number."),
+    }
+}
+
+// Settings that can be given several times take either one value or an array of them
+fn as_os_strings(left: &str, value: &Value) -> Result<Vec<OsString>, Error> {
+    match value {
+        Value::Array(array) => array.iter().map(|item| as_os_string(left, item)).collect(),
+        _ => Ok(vec![as_os_string(left, value)?]),
+    }
+}
+
+fn as_bool(left: &str, value: &Value) -> Result<bool, Error> {
+    match value {
+        Value::Boolean(boolean) => Ok(*boolean),
+        _ => fail(left, "Expected a boolean."),
+    }
+}
+
+fn apply_added_str(file: &str, table_name: &str, added_str: &mut AddedStr, value: &Value) -> Result<(), Error> {
+    let table: &Table = match value {
+        Value::Table(table) => table,
+        _ => return fail(&format!("{}: {}", file, table_name), "Expected a table."),
+    };
+
+    for (key, value) in table {
+        let left: String = format!("{}: {}.{}", file, table_name, key);
+        let added: Option<OsString> = Some(as_os_string(&left, value)?);
+
+        match key.as_str() {
+            "files" => added_str.files = added,
+            "directories" => added_str.directories = added,
+            "sticky_directories" => added_str.sticky_directories = added,
+            "other_writable_directories" => added_str.other_writable_directories = added,
+            "executables" => added_str.executables = added,
+            "setuid" => added_str.setuid = added,
+            "setgid" => added_str.setgid = added,
+            "symlinks" => added_str.symlinks = added,
+            "broken_symlinks" => added_str.broken_symlinks = added,
+            "fifos" => added_str.fifos = added,
+            "sockets" => added_str.sockets = added,
+            "block_devices" => added_str.block_devices = added,
+            "char_devices" => added_str.char_devices = added,
+            "unknowns" => added_str.unknowns = added,
+            _ => return fail(&left, "Unknown key."),
+        }
+    }
+    Ok(())
+}
+
+fn apply_key(config: &mut Config, file: &str, key: &str, value: &Value) -> Result<(), Error> {
+    let left: String = format!("{}: {}", file, key);
+    let left: &str = &left;
+
+    match key {
+        "titles" => config.titles = subparsers::formatting_args(left, as_os_string(left, value)?)?,
+        "files" => config.files = subparsers::formatting_args(left, as_os_string(left, value)?)?,
+        "directories" => {
+            config.directories = subparsers::formatting_args(left, as_os_string(left, value)?)?
+        }
+        "sticky_directories" => {
+            config.sticky_directories = subparsers::formatting_args(left, as_os_string(left, value)?)?
+        }
+        "other_writable_directories" => {
+            config.other_writable_directories = subparsers::formatting_args(left, as_os_string(left, value)?)?
+        }
+        "executables" => {
+            config.executables = subparsers::formatting_args(left, as_os_string(left, value)?)?
+        }
+        "setuid" => config.setuid = subparsers::formatting_args(left, as_os_string(left, value)?)?,
+        "setgid" => config.setgid = subparsers::formatting_args(left, as_os_string(left, value)?)?,
+        "symlinks" => config.symlinks = subparsers::formatting_args(left, as_os_string(left, value)?)?,
+        "broken_symlinks" => {
+            config.broken_symlinks = subparsers::formatting_args(left, as_os_string(left, value)?)?
+        }
+        "fifos" => config.fifos = subparsers::formatting_args(left, as_os_string(left, value)?)?,
+        "sockets" => config.sockets = subparsers::formatting_args(left, as_os_string(left, value)?)?,
+        "block_devices" => {
+            config.block_devices = subparsers::formatting_args(left, as_os_string(left, value)?)?
+        }
+        "char_devices" => {
+            config.char_devices = subparsers::formatting_args(left, as_os_string(left, value)?)?
+        }
+        "unknowns" => config.unknowns = subparsers::formatting_args(left, as_os_string(left, value)?)?,
+
+        "prefix" => apply_added_str(file, key, &mut config.prefix, value)?,
+        "suffix" => apply_added_str(file, key, &mut config.suffix, value)?,
+        "classify" => config.classify = as_bool(left, value)?,
+
+        "color" => {
+            config.colour_depth = ColourDepth::detect(subparsers::colour_when(left, as_os_string(left, value)?)?)
+        }
+        "color_seed" => config.color_seed = subparsers::color_seed(left, as_os_string(left, value)?)?,
+        "ls_colors" => {
+            config.ls_colors = if as_bool(left, value)? { Some(subparsers::ls_colors_env()) } else { None };
+        }
+        "dircolors" => config.ls_colors = Some(subparsers::dircolors(left, as_os_string(left, value)?)?),
+        "theme" => config.theme = Some(subparsers::theme(left, as_os_string(left, value)?)?),
+        "icons" => config.icons = subparsers::icon_set(left, as_os_string(left, value)?)?,
+        "lightness_range" => {
+            config.lightness_range = subparsers::lightness_range(left, as_os_string(left, value)?)?
+        }
+
+        "one_per_line" => config.one_per_line = as_bool(left, value)?,
+        "is_long_listing" => config.is_long_listing = as_bool(left, value)?,
+        "tree" => config.tree = as_bool(left, value)?,
+        "ascii_guides" => config.ascii_guides = as_bool(left, value)?,
+        "git_status" => config.git_status = as_bool(left, value)?,
+        "output_format" => {
+            config.output_format = subparsers::output_format(left, as_os_string(left, value)?)?
+        }
+
+        "time_formatting" => config.time_formatting = as_os_string(left, value)?,
+        "unit_size" => config.unit_size = subparsers::unit_size(left, as_os_string(left, value)?)?,
+        "size_precision" => {
+            config.size_precision = subparsers::size_precision(left, as_os_string(left, value)?)?
+        }
+        "thousands_separator" => config.thousands_separator = Some(as_os_string(left, value)?),
+
+        "sort_by" => config.sort_by = subparsers::sort_by(left, as_os_string(left, value)?)?,
+        "name_order" => {
+            config.collation.name_order = subparsers::name_order(left, as_os_string(left, value)?)?
+        }
+        "ignore_case" => config.collation.ignore_case = as_bool(left, value)?,
+        "normalization" => {
+            config.collation.normalization = subparsers::normalization(left, as_os_string(left, value)?)?
+        }
+        "ignore_leading_punctuation" => config.collation.ignore_leading_punctuation = as_bool(left, value)?,
+        "group_directories_first" => config.group_directories_first = as_bool(left, value)?,
+        "reverse" => config.reverse = as_bool(left, value)?,
+
+        "separator" => config.separator = as_os_string(left, value)?,
+        "padding" => config.padding = subparsers::padding(left, as_os_string(left, value)?)?,
+
+        "show_dotfiles" => config.show_dotfiles = as_bool(left, value)?,
+        "show_backups" => config.show_backups = as_bool(left, value)?,
+        "only_broken_symlinks" => config.only_broken_symlinks = as_bool(left, value)?,
+        "link_targets" => config.link_targets = as_bool(left, value)?,
+
+        "recursive" => config.recursive = as_bool(left, value)?,
+        "follow_symlinks" => config.follow_symlinks = as_bool(left, value)?,
+        "max_depth" => config.max_depth = Some(subparsers::max_depth(left, as_os_string(left, value)?)?),
+        "traversal" => config.traversal = subparsers::traversal(left, as_os_string(left, value)?)?,
+        "threads" => config.threads = subparsers::threads(left, as_os_string(left, value)?)?,
+
+        "include_pattern" => {
+            for pattern in as_os_strings(left, value)? {
+                config.include_patterns.push(subparsers::regex_patterns(left, pattern)?);
+            }
+        }
+        "exclude_pattern" => {
+            for pattern in as_os_strings(left, value)? {
+                config.exclude_patterns.push(subparsers::regex_patterns(left, pattern)?);
+            }
+        }
+        "include" => {
+            for pattern in as_os_strings(left, value)? {
+                config.include_patterns.push(subparsers::glob_patterns(left, pattern)?);
+            }
+        }
+        "exclude" => {
+            for pattern in as_os_strings(left, value)? {
+                config.exclude_patterns.push(subparsers::glob_patterns(left, pattern)?);
+            }
+        }
+        "ignore_pattern_case" => config.ignore_pattern_case = as_bool(left, value)?,
+
+        "term_width" => config.term_width = subparsers::width(left, as_os_string(left, value)?)?,
+
+        _ => return fail(left, "Unknown key."),
+    }
+    Ok(())
+}
+
+/// Layers the settings found in `path` on top of `config`.
+///
+/// Keys are named after the `Config` fields and go through the same subparsers as the command
+/// line. A missing file is not an error when `required` is false, which is how the default
+/// location is treated.
+pub fn apply(config: &mut Config, path: &Path, required: bool) -> Result<(), Error> {
+    let file: String = path.display().to_string();
+
+    let contents: String = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == ErrorKind::NotFound && !required => return Ok(()),
+        Err(error) => return fail(&file, &format!("Failed to read the config file: {}", error)),
+    };
+
+    let table: Table = match contents.parse::<Table>() {
+        Ok(table) => table,
+        Err(error) => return fail(&file, &format!("Failed to parse the config file: {}", error)),
+    };
+
+    // Keys come sorted, but `dircolors` takes precedence over `ls_colors` like on the command line
+    let dircolors = table.get_key_value("dircolors");
+    for (key, value) in table.iter().filter(|(key, _)| *key != "dircolors").chain(dircolors) {
+        apply_key(config, &file, key, value)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use std::io::{self, Error, Write};
+use std::path::Path;
+use std::time::SystemTime;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{json, Value};
+
+use crate::types::{ColouredEntry, Kind};
+
+use super::Listing;
+
+/// Bumped whenever a field is renamed, removed or changes meaning; new fields don't bump it.
+pub const SCHEMA_VERSION: u64 = 1;
+
+fn kind_name(kind: &Kind) -> &'static str {
+    match kind {
+        Kind::Directory => "directory",
+        Kind::StickyDirectory => "sticky_directory",
+        Kind::OtherWritableDirectory => "other_writable_directory",
+        Kind::File => "file",
+        Kind::Executable => "executable",
+        Kind::Setuid => "setuid",
+        Kind::Setgid => "setgid",
+        Kind::Symlink => "symlink",
+        Kind::BrokenSymlink => "broken_symlink",
+        Kind::Fifo => "fifo",
+        Kind::Socket => "socket",
+        Kind::BlockDevice => "block_device",
+        Kind::CharDevice => "char_device",
+        Kind::Unknown => "unknown",
+    }
+}
+
+fn timestamp(time: Option<SystemTime>) -> Value {
+    match time {
+        Some(time) => {
+            let datetime: DateTime<Utc> = DateTime::from(time);
+            Value::from(datetime.to_rfc3339_opts(SecondsFormat::Nanos, true))
+        }
+        None => Value::Null,
+    }
+}
+
+pub fn entry_record(entry: &ColouredEntry) -> Value {
+    json!({
+        "name": entry.name.to_string_lossy(),
+        "path": entry.path.to_string_lossy(),
+        "kind": kind_name(&entry.kind),
+        "extension": entry.extension.as_ref().map(|extension| extension.to_string_lossy()),
+        "size": entry.size_bytes,
+        "created": timestamp(entry.created_at),
+        "modified": timestamp(entry.modified_at),
+        "accessed": timestamp(entry.accessed_at),
+        "colour": entry.colour.to_hex(),
+        "targets": entry.link_targets.iter().map(|target| target.to_string_lossy()).collect::<Vec<_>>(),
+        "git": entry.git_status.map(|status| json!({
+            "conflicted": status.conflicted,
+            "staged": status.staged,
+            "modified": status.modified,
+            "untracked": status.untracked,
+            "ignored": status.ignored,
+        })),
+    })
+}
+
+pub fn error_record(error: &Error) -> Value {
+    json!({
+        "message": error.to_string(),
+        "code": error.raw_os_error(),
+    })
+}
+
+fn listed_records(listing: &Listing) -> (Vec<Value>, Vec<Value>) {
+    (
+        listing.entries.iter().map(entry_record).collect(),
+        listing.errors.iter().map(error_record).collect(),
+    )
+}
+
+/// Prints a single ndjson record, tagged with its `type` and the `directory` it was listed from.
+pub fn print_record<W: Write>(out: &mut W, directory: &Path, record_type: &str, mut record: Value) -> io::Result<()> {
+    record["version"] = Value::from(SCHEMA_VERSION);
+    record["type"] = Value::from(record_type);
+    record["directory"] = Value::from(directory.to_string_lossy());
+
+    writeln!(out, "{}", record)
+}
+
+/// Prints one self-describing record per line.
+pub fn print_records<W: Write>(out: &mut W, listing: &Listing) -> io::Result<()> {
+    let (entries, errors): (Vec<Value>, Vec<Value>) = listed_records(listing);
+
+    for entry in entries {
+        print_record(out, &listing.path, "entry", entry)?;
+    }
+    for error in errors {
+        print_record(out, &listing.path, "error", error)?;
+    }
+    Ok(())
+}
+
+/// Directories gathered for `--output json`, printed as a single document once listing ends.
+#[derive(Default)]
+pub struct JsonDocument {
+    directories: Vec<Value>,
+}
+
+impl JsonDocument {
+    pub fn add(&mut self, listing: &Listing) {
+        let (entries, errors): (Vec<Value>, Vec<Value>) = listed_records(listing);
+
+        self.directories.push(json!({
+            "path": listing.path.to_string_lossy(),
+            "entries": entries,
+            "errors": errors,
+        }));
+    }
+
+    pub fn print<W: Write>(self, out: &mut W) -> io::Result<()> {
+        let document: Value = json!({
+            "version": SCHEMA_VERSION,
+            "directories": self.directories,
+        });
+
+        writeln!(out, "{}", document)
+    }
+}
@@ -0,0 +1,57 @@
+use std::fs::{self, DirEntry};
+use std::io::Error;
+use std::path::{Path, PathBuf};
+
+use crate::types::{ColouredEntry, Config};
+
+use super::utils;
+
+/// A directory that has been read, stat'ed and sorted, ready to be printed by any layout.
+pub struct Listing {
+    pub path: PathBuf,
+    pub entries: Vec<ColouredEntry>,
+    pub errors: Vec<Error>,
+    /// Directories a recursive listing descends into, in the order they are displayed, including
+    /// those filtered out of `entries`.
+    pub subdirectories: Vec<PathBuf>,
+}
+
+impl Listing {
+    /// Reads `path` and builds its entries; a directory that can't be opened ends up as an error.
+    pub fn read(config: &Config, path: &Path) -> Self {
+        let read_dir: Vec<Result<DirEntry, Error>> = match fs::read_dir(path) {
+            Ok(read_dir) => read_dir.collect(),
+            Err(error) => vec![Err(error)],
+        };
+
+        let mut dir_entries: Vec<DirEntry> = Vec::new();
+        let mut errors: Vec<Error> = Vec::new();
+
+        for entry in read_dir {
+            match entry {
+                Ok(dir_entry) => dir_entries.push(dir_entry),
+                Err(error) => errors.push(error),
+            }
+        }
+
+        let colored_entries: Vec<ColouredEntry> =
+            utils::make_coloured_entries(config, dir_entries.iter().collect());
+
+        let mut entries: Vec<ColouredEntry> = utils::sort_entries(config, colored_entries);
+
+        let subdirectories: Vec<PathBuf> = entries
+            .iter()
+            .filter(|entry| entry.kind.is_directory())
+            .map(|entry| entry.path.clone())
+            .collect();
+
+        entries.retain(|entry| utils::is_shown(config, entry));
+
+        Listing {
+            path: path.to_path_buf(),
+            entries,
+            errors,
+            subdirectories,
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::mem;
+use std::ptr;
+use std::time::SystemTime;
+
+use chrono::{DateTime, Local};
+
+use crate::types::{ColouredEntry, Config};
+
+use super::size;
+
+const UNKNOWN_FIELD: &str = "?";
+
+struct Row {
+    permissions: String,
+    links: String,
+    owner: String,
+    group: String,
+    size: String,
+    modified_at: String,
+    git_status: Option<String>,
+}
+
+// POSIX `st_mode` bits, spelled out because `libc::mode_t` differs in width between platforms
+const S_IFMT: u32 = 0o170000;
+const S_IFSOCK: u32 = 0o140000;
+const S_IFLNK: u32 = 0o120000;
+const S_IFBLK: u32 = 0o060000;
+const S_IFDIR: u32 = 0o040000;
+const S_IFCHR: u32 = 0o020000;
+const S_IFIFO: u32 = 0o010000;
+const S_ISUID: u32 = 0o4000;
+const S_ISGID: u32 = 0o2000;
+const S_ISVTX: u32 = 0o1000;
+
+fn file_type_char(mode: u32) -> char {
+    match mode & S_IFMT {
+        S_IFDIR => 'd',
+        S_IFLNK => 'l',
+        S_IFIFO => 'p',
+        S_IFSOCK => 's',
+        S_IFBLK => 'b',
+        S_IFCHR => 'c',
+        _ => '-',
+    }
+}
+
+fn permission_triplet(mode: u32, shift: u32, special: u32, special_chars: (char, char)) -> [char; 3] {
+    let bits: u32 = (mode >> shift) & 0o7;
+
+    let read: char = if bits & 0o4 != 0 { 'r' } else { '-' };
+    let write: char = if bits & 0o2 != 0 { 'w' } else { '-' };
+
+    let execute: char = match (bits & 0o1 != 0, mode & special != 0) {
+        (true, true) => special_chars.0,
+        (false, true) => special_chars.1,
+        (true, false) => 'x',
+        (false, false) => '-',
+    };
+
+    [read, write, execute]
+}
+
+fn format_permissions(mode: Option<u32>) -> String {
+    let mode: u32 = match mode {
+        Some(mode) => mode,
+        None => return String::from("?---------"),
+    };
+
+    let mut permissions: String = String::with_capacity(10);
+    permissions.push(file_type_char(mode));
+    permissions.extend(&permission_triplet(mode, 6, S_ISUID, ('s', 'S')));
+    permissions.extend(&permission_triplet(mode, 3, S_ISGID, ('s', 'S')));
+    permissions.extend(&permission_triplet(mode, 0, S_ISVTX, ('t', 'T')));
+    permissions
+}
+
+fn lookup_user(uid: u32) -> Option<String> {
+    let mut passwd: libc::passwd = unsafe { mem::zeroed() };
+    let mut result: *mut libc::passwd = ptr::null_mut();
+    let mut buffer: Vec<libc::c_char> = vec![0; 1024];
+
+    let status = unsafe {
+        libc::getpwuid_r(uid, &mut passwd, buffer.as_mut_ptr(), buffer.len(), &mut result)
+    };
+
+    if status != 0 || result.is_null() {
+        return None;
+    }
+
+    let name: &CStr = unsafe { CStr::from_ptr(passwd.pw_name) };
+    Some(name.to_string_lossy().into_owned())
+}
+
+fn lookup_group(gid: u32) -> Option<String> {
+    let mut group: libc::group = unsafe { mem::zeroed() };
+    let mut result: *mut libc::group = ptr::null_mut();
+    let mut buffer: Vec<libc::c_char> = vec![0; 1024];
+
+    let status = unsafe {
+        libc::getgrgid_r(gid, &mut group, buffer.as_mut_ptr(), buffer.len(), &mut result)
+    };
+
+    if status != 0 || result.is_null() {
+        return None;
+    }
+
+    let name: &CStr = unsafe { CStr::from_ptr(group.gr_name) };
+    Some(name.to_string_lossy().into_owned())
+}
+
+fn resolve_name(
+    cache: &mut HashMap<u32, String>,
+    id: Option<u32>,
+    lookup: fn(u32) -> Option<String>,
+) -> String {
+    match id {
+        Some(id) => cache
+            .entry(id)
+            .or_insert_with(|| lookup(id).unwrap_or_else(|| id.to_string()))
+            .clone(),
+        None => String::from(UNKNOWN_FIELD),
+    }
+}
+
+fn format_size(config: &Config, size_bytes: Option<usize>) -> String {
+    match size_bytes {
+        Some(size) => size::format_size(config, size),
+        None => String::from(UNKNOWN_FIELD),
+    }
+}
+
+fn format_time(config: &Config, time: Option<SystemTime>) -> String {
+    let time: SystemTime = match time {
+        Some(time) => time,
+        None => return String::from(UNKNOWN_FIELD),
+    };
+
+    let datetime: DateTime<Local> = DateTime::from(time);
+    let lossy_format: Cow<str> = config.time_formatting.to_string_lossy();
+
+    // An invalid strftime pattern makes chrono's Display fail instead of printing garbage
+    let mut formatted: String = String::new();
+    if write!(formatted, "{}", datetime.format(&lossy_format)).is_err() {
+        return String::from(UNKNOWN_FIELD);
+    }
+    formatted
+}
+
+fn make_row(
+    config: &Config,
+    entry: &ColouredEntry,
+    users: &mut HashMap<u32, String>,
+    groups: &mut HashMap<u32, String>,
+) -> Row {
+    Row {
+        permissions: format_permissions(entry.mode),
+        links: entry
+            .links
+            .map_or_else(|| String::from(UNKNOWN_FIELD), |links| links.to_string()),
+        owner: resolve_name(users, entry.uid, lookup_user),
+        group: resolve_name(groups, entry.gid, lookup_group),
+        size: format_size(config, entry.size_bytes),
+        modified_at: format_time(config, entry.modified_at),
+        git_status: entry.git_status.map(|status| {
+            config.colour_depth.paint(&status.column(), &status.colour(), &[])
+        }),
+    }
+}
+
+pub fn show<W: Write>(out: &mut W, entries: Vec<ColouredEntry>, config: &Config) -> io::Result<()> {
+    let mut users: HashMap<u32, String> = HashMap::new();
+    let mut groups: HashMap<u32, String> = HashMap::new();
+
+    let rows: Vec<Row> = entries
+        .iter()
+        .map(|entry| make_row(config, entry, &mut users, &mut groups))
+        .collect();
+
+    let links_width: usize = rows.iter().map(|row| row.links.len()).max().unwrap_or(0);
+    let owner_width: usize = rows.iter().map(|row| row.owner.chars().count()).max().unwrap_or(0);
+    let group_width: usize = rows.iter().map(|row| row.group.chars().count()).max().unwrap_or(0);
+    let size_width: usize = rows.iter().map(|row| row.size.chars().count()).max().unwrap_or(0);
+    let has_git_column: bool = rows.iter().any(|row| row.git_status.is_some());
+
+    for (entry, row) in entries.iter().zip(rows.iter()) {
+        let lossy_name: Cow<str> = entry.formatted_name.to_string_lossy();
+
+        let git_column: String = match &row.git_status {
+            Some(git_status) => format!("{} ", git_status),
+            None if has_git_column => String::from("   "),
+            None => String::new(),
+        };
+
+        writeln!(
+            out,
+            "{} {:>links_width$} {:<owner_width$} {:<group_width$} {:>size_width$} {} {}{}",
+            row.permissions,
+            row.links,
+            row.owner,
+            row.group,
+            row.size,
+            row.modified_at,
+            git_column,
+            lossy_name,
+            links_width = links_width,
+            owner_width = owner_width,
+            group_width = group_width,
+            size_width = size_width,
+        )?;
+    }
+    Ok(())
+}
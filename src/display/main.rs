@@ -1,53 +1,49 @@
-use std::borrow::Cow;
-use std::fs::DirEntry;
-use std::path::PathBuf;
-use std::io::Error;
-use std::process;
-use std::ffi::OsString;
+use std::io::Write;
+use std::path::Path;
 
-use crate::types::{ColouredEntry, Config};
+use crate::error::Error;
+use crate::types::{ColouredEntry, Config, OutputFormat};
 
-use super::{multiline, one_line, one_per_line, utils};
+use super::{json, long, multiline, one_line, one_per_line, tree, utils, Listing};
 
-pub fn display_path(config: &Config, path_buf: &PathBuf, read_dir: &Vec<Result<DirEntry, Error>>) {
-
-    utils::print_title(path_buf);
-
-    let (entries, errors): (Vec<&DirEntry>, Vec<&Error>) = utils::divide_entries(read_dir);
-
-    let mut colored_entries: Vec<ColouredEntry> = Vec::new();
-    let mut total_len: usize = 0;
-
-    for dir_entry in entries {
+/// Shows a listing with its title; `is_root` tells whether its path was given on the command line.
+pub fn display_path<W: Write>(out: &mut W, config: &Config, listing: Listing, is_root: bool) -> Result<(), Error> {
+    if config.output_format == OutputFormat::Ndjson {
+        json::print_records(out, &listing)?;
+    } else {
+        utils::print_title(out, config, &listing.path)?;
 
-        let file_name: OsString = dir_entry.file_name();
-        let lossy_file_name: Cow<str> = file_name.to_string_lossy(); 
+        show_entries(out, config, listing.entries)?;
+    }
 
-        if !utils::is_allowed_filename(config, lossy_file_name) {
-            continue;
-        }
+    utils::report_errors(out, &listing.path, listing.errors, is_root)?;
+    Ok(())
+}
 
-        let colored_entry: ColouredEntry = ColouredEntry::new(file_name, dir_entry, config);
-        total_len += colored_entry.len();
-        colored_entries.push(colored_entry);
-    }
-    
-    colored_entries = utils::sort_entries(config, colored_entries);
+/// Lays already sorted entries out with whichever layout `config` asks for, without a title.
+pub fn show_entries<W: Write>(out: &mut W, config: &Config, colored_entries: Vec<ColouredEntry>) -> Result<(), Error> {
+    let total_len: usize = colored_entries.iter().map(ColouredEntry::len).sum();
 
-    if config.one_per_line {
-        one_per_line::show(colored_entries);
+    if config.is_long_listing {
+        long::show(out, colored_entries, config)?;
+    } else if config.one_per_line {
+        one_per_line::show(out, colored_entries)?;
     } else if let Some(term_width) = config.term_width {
         if total_len < term_width {
-            one_line::show(colored_entries, config);
+            one_line::show(out, colored_entries, config)?;
         } else {
-            multiline::show(colored_entries, config);
+            multiline::show(out, colored_entries, config)?;
         }
     } else {
-        eprintln!("Failed to get terminal size and none was provided either.");
-        process::exit(1);
+        return Err(Error::argument(
+            "--width",
+            String::from("Failed to get terminal size and none was provided either."),
+        ));
     }
+    Ok(())
+}
 
-    for error in errors {
-        println!("{}", error);
-    }
+pub fn display_tree<W: Write>(out: &mut W, config: &Config, path: &Path) -> Result<(), Error> {
+    tree::show(out, config, path)?;
+    Ok(())
 }
@@ -1,8 +1,21 @@
 mod main;
 pub use main::*;
 
+mod json;
+pub use json::JsonDocument;
+
+mod listing;
+pub use listing::Listing;
+
+mod stream;
+pub use stream::{is_streamable, stream_path};
+
+mod size;
 mod utils;
+pub use utils::{report_errors, is_allowed_filename, is_allowed_kind, is_shown, make_coloured_entries, make_coloured_entry, sort_entries};
 
 mod one_per_line;
 mod multiline;
-mod one_line;
\ No newline at end of file
+mod one_line;
+mod long;
+mod tree;
\ No newline at end of file
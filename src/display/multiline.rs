@@ -1,7 +1,8 @@
 use std::borrow::Cow;
+use std::io::{self, Write};
 use crate::types::{ColouredEntry, Config};
 
-fn get_column_length(entries: &Vec<ColouredEntry>, num_columns: usize, column: usize) -> usize {
+fn get_column_length(entries: &[ColouredEntry], num_columns: usize, column: usize) -> usize {
     let num_rows: usize = (entries.len() / num_columns) + 1;
     let mut column_length: usize = 0;
     
@@ -14,7 +15,7 @@ fn get_column_length(entries: &Vec<ColouredEntry>, num_columns: usize, column: u
     column_length
 }
 
-fn get_column_lengths(config: &Config, entries: &Vec<ColouredEntry>) -> Vec<usize> {
+fn get_column_lengths(config: &Config, entries: &[ColouredEntry]) -> Vec<usize> {
     let mut best_column_lengths: Vec<usize> = Vec::new();
 
     // Wonder if there is some kind of formula that could help us 
@@ -23,7 +24,7 @@ fn get_column_lengths(config: &Config, entries: &Vec<ColouredEntry>) -> Vec<usiz
         let mut column_lengths: Vec<usize> = Vec::new();
 
         for column in 0..num_columns {
-            let column_length: usize = get_column_length(&entries, num_columns, column);
+            let column_length: usize = get_column_length(entries, num_columns, column);
             column_lengths.push(column_length);
         }
 
@@ -38,7 +39,7 @@ fn get_column_lengths(config: &Config, entries: &Vec<ColouredEntry>) -> Vec<usiz
     best_column_lengths
 }
 
-pub fn show(entries: Vec<ColouredEntry>, config: &Config) {
+pub fn show<W: Write>(out: &mut W, entries: Vec<ColouredEntry>, config: &Config) -> io::Result<()> {
 
     let column_sizes = get_column_lengths(config, &entries);
 
@@ -62,19 +63,18 @@ pub fn show(entries: Vec<ColouredEntry>, config: &Config) {
     for (inner_index, (entry, column_size)) in column_display_iterator {
 
         let lossy_name: Cow<str> = entry.formatted_name.to_string_lossy();
-        print!("{}", lossy_name);
+        write!(out, "{}", lossy_name)?;
 
         let diff: usize = column_size - entry.len();
-        for _ in 0..diff { 
-            print!("{}", lossy_padding);
-        }
+        write!(out, "{}", lossy_padding.repeat(diff))?;
 
         // There must be a way to directly get the index of that one
         if inner_index != no_separator_index {
-            print!("{}", lossy_sep);
+            write!(out, "{}", lossy_sep)?;
         }
     }
-    println!();
+    writeln!(out)?;
     }
+    Ok(())
 }
 
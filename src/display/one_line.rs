@@ -1,13 +1,14 @@
 use std::borrow::Cow;
+use std::io::{self, Write};
 use crate::types::{ColouredEntry, Config};
 
-pub fn show(colored_entries: Vec<ColouredEntry>, config: &Config) {
+pub fn show<W: Write>(out: &mut W, colored_entries: Vec<ColouredEntry>, config: &Config) -> io::Result<()> {
 
     let lossy_sep: Cow<str> = config.separator.to_string_lossy();
 
     for entry in colored_entries {
         let lossy_name: Cow<str>  = entry.formatted_name.to_string_lossy();
-        print!("{}{}", lossy_name, lossy_sep);
+        write!(out, "{}{}", lossy_name, lossy_sep)?;
     }
-    println!();
-}
\ No newline at end of file
+    writeln!(out)
+}
@@ -1,10 +1,12 @@
 use std::borrow::Cow;
+use std::io::{self, Write};
 use crate::types::ColouredEntry;
 
-pub fn show(colored_entries: Vec<ColouredEntry>) {
+pub fn show<W: Write>(out: &mut W, colored_entries: Vec<ColouredEntry>) -> io::Result<()> {
     for entry in colored_entries {
         let lossy_name: Cow<str> = entry.formatted_name.to_string_lossy();
-        println!("{}", lossy_name);
+        writeln!(out, "{}", lossy_name)?;
     }
+    Ok(())
 }
 
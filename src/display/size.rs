@@ -0,0 +1,168 @@
+use std::borrow::Cow;
+
+use crate::types::{Config, SizeMeasurementUnit};
+
+const SI_BYTES: [&str; 7] = ["B", "kB", "MB", "GB", "TB", "PB", "EB"];
+const SI_BITS: [&str; 7] = ["b", "kb", "Mb", "Gb", "Tb", "Pb", "Eb"];
+const IEC_BYTES: [&str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+const IEC_BITS: [&str; 7] = ["b", "Kib", "Mib", "Gib", "Tib", "Pib", "Eib"];
+
+fn is_bits(unit: &SizeMeasurementUnit) -> bool {
+    matches!(
+        unit,
+        SizeMeasurementUnit::Bits | SizeMeasurementUnit::SiBits | SizeMeasurementUnit::IecBits
+    )
+}
+
+fn scale(unit: &SizeMeasurementUnit) -> Option<(f64, &'static [&'static str; 7])> {
+    match unit {
+        SizeMeasurementUnit::Bytes | SizeMeasurementUnit::Bits => None,
+        SizeMeasurementUnit::SiBytes => Some((1000.0, &SI_BYTES)),
+        SizeMeasurementUnit::SiBits => Some((1000.0, &SI_BITS)),
+        SizeMeasurementUnit::IecBytes => Some((1024.0, &IEC_BYTES)),
+        SizeMeasurementUnit::IecBits => Some((1024.0, &IEC_BITS)),
+    }
+}
+
+fn group_thousands(digits: &str, separator: &str) -> String {
+    let mut grouped: String = String::with_capacity(digits.len() + digits.len() / 3);
+
+    for (index, chr) in digits.chars().enumerate() {
+        if index != 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push_str(separator);
+        }
+        grouped.push(chr);
+    }
+
+    grouped
+}
+
+fn separate_integer_part(config: &Config, number: String) -> String {
+    let separator: Cow<str> = match &config.thousands_separator {
+        Some(separator) => separator.to_string_lossy(),
+        None => return number,
+    };
+
+    match number.find('.') {
+        Some(dot) => {
+            let (integer, fraction) = number.split_at(dot);
+            group_thousands(integer, &separator) + fraction
+        }
+        None => group_thousands(&number, &separator),
+    }
+}
+
+// What `value` reads as once printed with `precision` decimals
+fn rounded(value: f64, precision: usize) -> f64 {
+    format!("{:.*}", precision, value).parse::<f64>().unwrap_or(value)
+}
+
+/// Renders a size in bytes using the unit, precision and separator from the config.
+///
+/// Raw units print the exact count, scaled ones divide it by powers of 1000 (SI) or 1024 (IEC)
+/// until it fits, e.g. `1468006` becomes `1.4 MiB`.
+pub fn format_size(config: &Config, size_bytes: usize) -> String {
+    let amount: u128 = if is_bits(&config.unit_size) {
+        size_bytes as u128 * 8
+    } else {
+        size_bytes as u128
+    };
+
+    let (base, units) = match scale(&config.unit_size) {
+        Some(scale) => scale,
+        None => return separate_integer_part(config, amount.to_string()),
+    };
+
+    let mut value: f64 = amount as f64;
+    let mut unit_index: usize = 0;
+
+    while value >= base && unit_index < units.len() - 1 {
+        value /= base;
+        unit_index += 1;
+    }
+
+    // Rounding can carry the value up to the base, 999999 bytes make `1 MB` rather than `1000 kB`
+    if unit_index < units.len() - 1 && rounded(value, config.size_precision) >= base {
+        value /= base;
+        unit_index += 1;
+    }
+
+    let number: String = if unit_index == 0 {
+        amount.to_string()
+    } else {
+        format!("{:.*}", config.size_precision, value)
+    };
+
+    format!("{} {}", separate_integer_part(config, number), units[unit_index])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsString;
+
+    use super::*;
+
+    fn config(unit_size: SizeMeasurementUnit, size_precision: usize) -> Config {
+        Config {
+            unit_size,
+            size_precision,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn raw_units_print_exact_counts() {
+        let mut config: Config = config(SizeMeasurementUnit::Bytes, 1);
+        assert_eq!(format_size(&config, 1234567), "1234567");
+
+        config.thousands_separator = Some(OsString::from(","));
+        assert_eq!(format_size(&config, 1234567), "1,234,567");
+
+        config.unit_size = SizeMeasurementUnit::Bits;
+        assert_eq!(format_size(&config, 1000), "8,000");
+    }
+
+    #[test]
+    fn sizes_below_the_base_stay_in_bytes() {
+        assert_eq!(format_size(&config(SizeMeasurementUnit::SiBytes, 1), 999), "999 B");
+        assert_eq!(format_size(&config(SizeMeasurementUnit::IecBytes, 1), 1023), "1023 B");
+    }
+
+    #[test]
+    fn reaching_the_base_moves_up_a_unit() {
+        assert_eq!(format_size(&config(SizeMeasurementUnit::SiBytes, 1), 1000), "1.0 kB");
+        assert_eq!(format_size(&config(SizeMeasurementUnit::IecBytes, 1), 1024), "1.0 KiB");
+        assert_eq!(format_size(&config(SizeMeasurementUnit::SiBits, 1), 125), "1.0 kb");
+    }
+
+    #[test]
+    fn precision_sets_the_decimals() {
+        assert_eq!(format_size(&config(SizeMeasurementUnit::IecBytes, 0), 1536), "2 KiB");
+        assert_eq!(format_size(&config(SizeMeasurementUnit::IecBytes, 1), 1468006), "1.4 MiB");
+        assert_eq!(format_size(&config(SizeMeasurementUnit::IecBytes, 2), 1536), "1.50 KiB");
+    }
+
+    #[test]
+    fn rounding_up_to_the_base_moves_up_a_unit() {
+        assert_eq!(format_size(&config(SizeMeasurementUnit::SiBytes, 0), 999_499), "999 kB");
+        assert_eq!(format_size(&config(SizeMeasurementUnit::SiBytes, 0), 999_999), "1 MB");
+        assert_eq!(format_size(&config(SizeMeasurementUnit::SiBytes, 1), 999_949), "999.9 kB");
+        assert_eq!(format_size(&config(SizeMeasurementUnit::SiBytes, 1), 999_950), "1.0 MB");
+        assert_eq!(format_size(&config(SizeMeasurementUnit::IecBytes, 0), 1_048_063), "1023 KiB");
+        assert_eq!(format_size(&config(SizeMeasurementUnit::IecBytes, 0), 1_048_064), "1 MiB");
+        assert_eq!(format_size(&config(SizeMeasurementUnit::IecBytes, 1), 1_048_575), "1.0 MiB");
+    }
+
+    #[test]
+    fn the_largest_unit_is_never_exceeded() {
+        assert_eq!(format_size(&config(SizeMeasurementUnit::IecBytes, 1), usize::MAX), "16.0 EiB");
+        assert_eq!(format_size(&config(SizeMeasurementUnit::SiBytes, 0), usize::MAX), "18 EB");
+    }
+
+    #[test]
+    fn separators_group_the_integer_part_only() {
+        let mut config: Config = config(SizeMeasurementUnit::IecBytes, 1);
+        config.thousands_separator = Some(OsString::from(","));
+        assert_eq!(format_size(&config, 1_048_064), "1,023.5 KiB");
+    }
+}
@@ -0,0 +1,94 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::{self, DirEntry, ReadDir};
+use std::io::{self, Error, Write};
+use std::path::{Path, PathBuf};
+
+use crate::types::{ColouredEntry, Config, GitStatus, OutputFormat};
+
+use super::{json, utils};
+
+/// Whether entries can be printed straight out of `read_dir`: nothing reorders them and the
+/// layout never needs to see the whole directory to line them up.
+pub fn is_streamable(config: &Config) -> bool {
+    if !config.sort_by.is_empty() || config.group_directories_first || config.reverse {
+        return false;
+    }
+
+    match config.output_format {
+        OutputFormat::Text => config.one_per_line && !config.is_long_listing,
+        OutputFormat::Ndjson => true,
+        OutputFormat::Json => false,
+    }
+}
+
+fn print_entry<W: Write>(out: &mut W, config: &Config, path: &Path, entry: &ColouredEntry) -> io::Result<()> {
+    if config.output_format == OutputFormat::Ndjson {
+        json::print_record(out, path, "entry", json::entry_record(entry))
+    } else {
+        let lossy_name: Cow<str> = entry.formatted_name.to_string_lossy();
+        writeln!(out, "{}", lossy_name)
+    }
+}
+
+fn print_error<W: Write>(out: &mut W, config: &Config, path: &Path, error: Error, is_root: bool) -> io::Result<()> {
+    if config.output_format == OutputFormat::Ndjson {
+        json::print_record(out, path, "error", json::error_record(&error))?;
+    }
+    utils::report_errors(out, path, vec![error], is_root)
+}
+
+/// Prints every entry of `path` as soon as it is read, keeping only one in memory at a time.
+///
+/// Returns the subdirectories met on the way, in the order they were printed, for recursion.
+pub fn stream_path<W: Write>(out: &mut W, config: &Config, path: &Path, is_root: bool) -> io::Result<Vec<PathBuf>> {
+    if config.output_format == OutputFormat::Text {
+        utils::print_title(out, config, path)?;
+    }
+
+    let read_dir: ReadDir = match fs::read_dir(path) {
+        Ok(read_dir) => read_dir,
+        Err(error) => {
+            print_error(out, config, path, error, is_root)?;
+            return Ok(Vec::new());
+        }
+    };
+
+    let git_statuses: Option<HashMap<OsString, GitStatus>> = if config.git_status {
+        GitStatus::for_directory(path)
+    } else {
+        None
+    };
+
+    let mut subdirectories: Vec<PathBuf> = Vec::new();
+
+    for result in read_dir {
+        let dir_entry: DirEntry = match result {
+            Ok(dir_entry) => dir_entry,
+            Err(error) => {
+                print_error(out, config, path, error, is_root)?;
+                continue;
+            }
+        };
+
+        let mut entry: ColouredEntry = match utils::make_coloured_entry(config, &dir_entry) {
+            Some(entry) => entry,
+            None => continue,
+        };
+
+        if let Some(statuses) = &git_statuses {
+            utils::add_git_status(config, &mut entry, statuses);
+        }
+
+        if utils::is_shown(config, &entry) {
+            print_entry(out, config, path, &entry)?;
+        }
+
+        if entry.kind.is_directory() {
+            subdirectories.push(entry.path);
+        }
+    }
+
+    Ok(subdirectories)
+}
@@ -0,0 +1,81 @@
+use std::borrow::Cow;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::types::{Config, Visited};
+
+use super::{utils, Listing};
+
+struct Guides {
+    branch: &'static str,
+    last_branch: &'static str,
+    vertical: &'static str,
+    blank: &'static str,
+}
+
+const UNICODE_GUIDES: Guides = Guides {
+    branch: "├── ",
+    last_branch: "└── ",
+    vertical: "│   ",
+    blank: "    ",
+};
+
+const ASCII_GUIDES: Guides = Guides {
+    branch: "|-- ",
+    last_branch: "`-- ",
+    vertical: "|   ",
+    blank: "    ",
+};
+
+fn show_children<W: Write>(
+    out: &mut W,
+    config: &Config,
+    guides: &Guides,
+    path: &Path,
+    indent: &str,
+    depth: usize,
+    ancestors: &mut Visited,
+) -> io::Result<()> {
+    let Listing { entries, errors, .. } = Listing::read(config, path);
+
+    utils::report_errors(out, path, errors, depth == 0)?;
+
+    for (index, entry) in entries.iter().enumerate() {
+        let is_last: bool = index + 1 == entries.len();
+        let branch: &str = if is_last { guides.last_branch } else { guides.branch };
+
+        let lossy_name: Cow<str> = entry.formatted_name.to_string_lossy();
+        writeln!(out, "{}{}{}", indent, branch, lossy_name)?;
+
+        if !entry.kind.is_directory() || config.max_depth.is_some_and(|max_depth| depth + 1 >= max_depth) {
+            continue;
+        }
+
+        // Only reachable through followed symlinks, but those can point back up the tree
+        if !ancestors.insert(&entry.path) {
+            if Visited::is_link(&entry.path) {
+                // Right below the entry it is about, not before the whole tree once stdout is flushed
+                out.flush()?;
+                Visited::report(&entry.path);
+            }
+            continue;
+        }
+
+        let child_indent: String = format!("{}{}", indent, if is_last { guides.blank } else { guides.vertical });
+        show_children(out, config, guides, &entry.path, &child_indent, depth + 1, ancestors)?;
+
+        ancestors.remove(&entry.path);
+    }
+    Ok(())
+}
+
+pub fn show<W: Write>(out: &mut W, config: &Config, path: &Path) -> io::Result<()> {
+    let guides: &Guides = if config.ascii_guides { &ASCII_GUIDES } else { &UNICODE_GUIDES };
+
+    writeln!(out, "{}", path.display())?;
+
+    let mut ancestors: Visited = Visited::default();
+    ancestors.insert(path);
+
+    show_children(out, config, guides, path, "", 0, &mut ancestors)
+}
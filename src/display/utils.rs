@@ -1,45 +1,114 @@
 use std::borrow::Cow;
-use std::env;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::ffi::OsString;
 use std::fs::DirEntry;
-use std::io::Error;
-use std::path::PathBuf;
+use std::io::{self, Write};
+use std::path::Path;
 
-use crate::types::{Config, ColouredEntry, Kind, SortingReference};
+use crate::error::{self, Error};
+use crate::types::{Collation, Config, ColouredEntry, GitStatus, Kind, NameOrder, Pattern, RgbColor, SortKey, SortingReference};
 
-pub fn  print_title(path_buf: &PathBuf) {
-    if let Ok(curr_dir) = env::current_dir() {
+/// `path` relative to the current directory when it lies inside of it, as is otherwise.
+pub fn  relative_path<'a>(config: &Config, path: &'a Path) -> &'a Path {
+    match &config.current_dir {
+        Some(curr_dir) => path.strip_prefix(curr_dir).unwrap_or(path),
+        None => path,
+    }
+}
 
-        if &curr_dir == path_buf { // nothing to do there
-            return;
-        }
+pub fn  print_title<W: Write>(out: &mut W, config: &Config, path_buf: &Path) -> io::Result<()> {
+    let relative: &Path = relative_path(config, path_buf);
 
-        if let Ok(stripped) = path_buf.strip_prefix(curr_dir) {
-            println!("{}", stripped.display())
-        } else {
-            println!("{}", path_buf.display())
-        }
-    } else {
-        println!("{}", path_buf.display())
+    if relative.as_os_str().is_empty() { // nothing to do there
+        return Ok(());
+    }
+
+    writeln!(out, "{}", relative.display())
+}
+
+/// Reports read errors met while listing `path` to stderr, as serious ones when `path` was given
+/// on the command line.
+///
+/// What was written so far is flushed first, so they show up next to the listing they belong to.
+pub fn  report_errors<W: Write>(out: &mut W, path: &Path, errors: Vec<io::Error>, is_root: bool) -> io::Result<()> {
+    if errors.is_empty() {
+        return Ok(());
     }
+
+    out.flush()?;
+
+    for source in errors {
+        error::report(&Error::Read { path: path.to_path_buf(), source, is_root });
+    }
+    Ok(())
+}
+
+/// Builds the entry unless it is filtered out by the dotfile, backup or exclude options.
+pub fn  make_coloured_entry(config: &Config, dir_entry: &DirEntry) -> Option<ColouredEntry> {
+
+    let file_name: OsString = dir_entry.file_name();
+    let lossy_file_name: Cow<str> = file_name.to_string_lossy();
+
+    if !is_allowed_filename(config, lossy_file_name, &dir_entry.path()) {
+        return None;
+    }
+
+    Some(ColouredEntry::new(file_name, dir_entry, config))
 }
 
-pub fn  divide_entries(read_dir: &Vec<Result<DirEntry, Error>>) -> (Vec<&DirEntry>, Vec<&Error>) {
+pub fn  make_coloured_entries(config: &Config, entries: Vec<&DirEntry>) -> Vec<ColouredEntry> {
 
-    let mut entries: Vec<&DirEntry> = Vec::new();
-    let mut errors: Vec<&Error> = Vec::new();
+    let mut colored_entries: Vec<ColouredEntry> = entries
+        .into_iter()
+        .filter_map(|dir_entry| make_coloured_entry(config, dir_entry))
+        .collect();
 
-    for entry in read_dir {
-        match entry {
-            Ok(dir_entry) => {
-                entries.push(dir_entry);
-            },
-            Err(error) => errors.push(error),
+    if config.git_status {
+        let directory: Option<&Path> = colored_entries.first().and_then(|entry| entry.path.parent());
+
+        if let Some(statuses) = directory.and_then(GitStatus::for_directory) {
+            for entry in colored_entries.iter_mut() {
+                add_git_status(config, entry, &statuses);
+            }
         }
     }
-    (entries, errors)
+
+    colored_entries
 }
 
-pub fn  is_allowed_filename(config: &Config, lossy_file_name: Cow<str>) -> bool {
+/// Stores the entry's status and, outside of the long listing, shows it as a marker.
+pub fn  add_git_status(config: &Config, entry: &mut ColouredEntry, statuses: &HashMap<OsString, GitStatus>) {
+
+    let status: GitStatus = statuses.get(&entry.name).copied().unwrap_or_default();
+    entry.git_status = Some(status);
+
+    // The long listing has a dedicated column
+    if !config.is_long_listing {
+        let marker: String = status.marker().to_string();
+        let styled_marker: String = if status == GitStatus::default() {
+            marker
+        } else {
+            config.colour_depth.paint(&marker, &status.colour(), &[1])
+        };
+        entry.add_marker(&format!("{} ", styled_marker), 2);
+    }
+}
+
+fn  matches(config: &Config, pattern: &Pattern, lossy_file_name: &str, path: &Path) -> bool {
+    if pattern.matches_path() {
+        let path: &Path = relative_path(config, path);
+        // `./src/main.rs` is what `src/*.rs` is meant for too
+        let lossy_path: String = path.strip_prefix(".").unwrap_or(path).to_string_lossy().into_owned();
+        pattern.is_match(&lossy_path, config.ignore_pattern_case)
+    } else {
+        pattern.is_match(lossy_file_name, config.ignore_pattern_case)
+    }
+}
+
+/// Name based filters, applied before building entries so that what they hide is never read
+/// again, recursive listings included.
+pub fn  is_allowed_filename(config: &Config, lossy_file_name: Cow<str>, path: &Path) -> bool {
 
     if !config.show_dotfiles && lossy_file_name.starts_with('.') {
         return false;
@@ -47,51 +116,155 @@ pub fn  is_allowed_filename(config: &Config, lossy_file_name: Cow<str>) -> bool
     if !config.show_backups && lossy_file_name.ends_with('~') {
         return false;
     }
-    if let Some(include_pattern) = &config.include_pattern {
-        return include_pattern.is_match(&lossy_file_name);
+
+    !config
+        .exclude_patterns
+        .iter()
+        .any(|pattern| matches(config, pattern, &lossy_file_name, path))
+}
+
+/// Whether a built entry is displayed, once the include patterns and kind filters are applied.
+///
+/// Unlike exclusions these only hide entries, recursive listings still descend into the
+/// directories they leave out.
+pub fn  is_shown(config: &Config, entry: &ColouredEntry) -> bool {
+    // Directories are what holds a tree together
+    if config.tree && entry.kind.is_directory() {
+        return true;
     }
-    if let Some(exclude_pattern) = &config.exclude_pattern {
-        return !exclude_pattern.is_match(&lossy_file_name);
+
+    let lossy_file_name: Cow<str> = entry.name.to_string_lossy();
+    let is_included: bool = config.include_patterns.is_empty()
+        || config
+            .include_patterns
+            .iter()
+            .any(|pattern| matches(config, pattern, &lossy_file_name, &entry.path));
+
+    is_included && is_allowed_kind(config, &entry.kind)
+}
+
+/// Kind based filters, applied once entries are built since the kind comes from their metadata.
+pub fn  is_allowed_kind(config: &Config, kind: &Kind) -> bool {
+
+    if config.only_broken_symlinks {
+        return *kind == Kind::BrokenSymlink || (config.tree && kind.is_directory());
     }
 
     true
 }
 
-pub fn  sort_entries(config: &Config, mut entries: Vec<ColouredEntry>) -> Vec<ColouredEntry> {
+// Below this chroma a colour reads as grey, whatever its hue says
+const ACHROMATIC_CHROMA: f64 = 0.02;
 
-    // Not vey elegant way to force dirs grouping if needed...
-    let sort_by  = |entry: &ColouredEntry| !(entry.kind == Kind::Directory && config.group_directories_first);
+// Greys have no meaningful hue and go after the rainbow, from dark to light
+fn compare_hues(left: &RgbColor, right: &RgbColor) -> Ordering {
+    let key = |colour: &RgbColor| -> (bool, f64, f64) {
+        let (lightness, chroma, hue): (f64, f64, f64) = colour.to_oklch();
+        if chroma < ACHROMATIC_CHROMA {
+            (true, 0.0, lightness)
+        } else {
+            (false, hue, lightness)
+        }
+    };
 
-    match config.sort_by {
-        SortingReference::AccessDate => {
-            entries.sort_unstable_by_key(|entry| (sort_by(entry), entry.accessed_at))
-        },
-        SortingReference::Colour => {
-            entries.sort_unstable_by_key(|entry| (sort_by(entry), entry.colour.as_tuple()))
-        },
-        SortingReference::CreationDate => {
-            entries.sort_unstable_by_key(|entry| (sort_by(entry), entry.created_at))
-        },
-        SortingReference::Extension => {
-            entries.sort_unstable_by_key(|entry| (sort_by(entry), entry.extension.clone()))
-        },
-        SortingReference::ModificationDate => {
-            entries.sort_unstable_by_key(|entry| (sort_by(entry), entry.modified_at))
-        },
-        SortingReference::Name => { 
-            entries.sort_unstable_by_key(|entry| (sort_by(entry), entry.name.clone()))
+    let ((left_grey, left_hue, left_lightness), (right_grey, right_hue, right_lightness)) = (key(left), key(right));
+
+    left_grey
+        .cmp(&right_grey)
+        .then(left_hue.total_cmp(&right_hue))
+        .then(left_lightness.total_cmp(&right_lightness))
+}
+
+/// An entry along with the collation keys of its name and extension, worked out once per sort
+/// rather than on every comparison.
+pub struct Keyed {
+    name: OsString,
+    extension: Option<OsString>,
+    entry: ColouredEntry,
+}
+
+impl Keyed {
+    fn new(collation: &Collation, entry: ColouredEntry) -> Self {
+        Keyed {
+            name: collation.key(&entry.name).into_owned(),
+            extension: entry.extension.as_deref().map(|extension| collation.key(extension).into_owned()),
+            entry,
+        }
+    }
+
+    fn compare_names(&self, collation: &Collation, other: &Keyed) -> Ordering {
+        collation.compare_keys(&self.entry.name, &self.name, &other.entry.name, &other.name)
+    }
+}
+
+fn compare_field(collation: &Collation, field: &SortingReference, left: &Keyed, right: &Keyed) -> Ordering {
+    let (left_entry, right_entry): (&ColouredEntry, &ColouredEntry) = (&left.entry, &right.entry);
+
+    match field {
+        SortingReference::AccessDate => left_entry.accessed_at.cmp(&right_entry.accessed_at),
+        SortingReference::Colour => left_entry.colour.as_tuple().cmp(&right_entry.colour.as_tuple()),
+        SortingReference::Hue => compare_hues(&left_entry.colour, &right_entry.colour),
+        SortingReference::CreationDate => left_entry.created_at.cmp(&right_entry.created_at),
+        SortingReference::Directories => (!left_entry.kind.is_directory()).cmp(&!right_entry.kind.is_directory()),
+        SortingReference::Extension => match (&left_entry.extension, &left.extension, &right_entry.extension, &right.extension) {
+            (Some(left_extension), Some(left_key), Some(right_extension), Some(right_key)) => {
+                collation.compare_keys(left_extension, left_key, right_extension, right_key)
+            }
+            _ => left_entry.extension.is_some().cmp(&right_entry.extension.is_some()),
         },
-        SortingReference::Size => {
-            entries.sort_unstable_by_key(|entry| (sort_by(entry), entry.size_bytes))
+        SortingReference::Kind => left_entry.kind.cmp(&right_entry.kind),
+        SortingReference::ModificationDate => left_entry.modified_at.cmp(&right_entry.modified_at),
+        SortingReference::Name => left.compare_names(collation, right),
+        SortingReference::Natural => {
+            let collation: Collation = Collation { name_order: NameOrder::Natural, ..*collation };
+            left.compare_names(&collation, right)
         },
-        SortingReference::Default => {
-            entries.sort_unstable();
+        SortingReference::Version => {
+            let collation: Collation = Collation { name_order: NameOrder::Version, ..*collation };
+            left.compare_names(&collation, right)
         },
+        SortingReference::Size => left_entry.size_bytes.cmp(&right_entry.size_bytes),
     }
-    if config.reverse {
-        entries.reverse()
-    }
+}
+
+/// Compares two entries on every sort key in turn.
+pub fn  compare_entries(config: &Config, sort_keys: &[SortKey], left: &Keyed, right: &Keyed) -> Ordering {
+    for sort_key in sort_keys {
+        let ordering: Ordering = compare_field(&config.collation, &sort_key.field, left, right);
 
-    entries
+        if ordering != Ordering::Equal {
+            return if sort_key.descending { ordering.reverse() } else { ordering };
+        }
+    }
+    Ordering::Equal
 }
 
+pub fn  sort_entries(config: &Config, mut entries: Vec<ColouredEntry>) -> Vec<ColouredEntry> {
+    let sort_keys: Vec<SortKey> = config.sort_keys();
+
+    // Unsorted, `reverse` flips the order the file system returned and grouping keeps it
+    if config.sort_by.is_empty() {
+        if config.reverse {
+            entries.reverse();
+        }
+        if sort_keys.is_empty() {
+            return entries;
+        }
+    }
+
+    let mut keyed: Vec<Keyed> = entries.into_iter().map(|entry| Keyed::new(&config.collation, entry)).collect();
+
+    if config.sort_by.is_empty() {
+        keyed.sort_by(|left, right| compare_entries(config, &sort_keys, left, right));
+    } else {
+        // Names are unique within a directory, so ties always end up broken the same way
+        keyed.sort_unstable_by(|left, right| {
+            compare_entries(config, &sort_keys, left, right).then_with(|| {
+                let ordering: Ordering = left.compare_names(&config.collation, right);
+                if config.reverse { ordering.reverse() } else { ordering }
+            })
+        });
+    }
+
+    keyed.into_iter().map(|keyed| keyed.entry).collect()
+}
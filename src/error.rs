@@ -0,0 +1,140 @@
+//! Errors shared by argument parsing, traversal and display, and the exit code they lead to.
+//!
+//! Exit codes follow GNU `ls`: 0 when everything went fine, 1 for minor problems such as a
+//! subdirectory that couldn't be read, and 2 for serious trouble such as invalid arguments or a
+//! path given on the command line that can't be listed.
+
+use std::env;
+use std::fmt;
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+
+pub const MINOR: i32 = 1;
+pub const SERIOUS: i32 = 2;
+
+// The worst problem reported so far, like the `exit_status` global of GNU `ls`
+static EXIT_CODE: AtomicI32 = AtomicI32::new(0);
+
+// Cleared by `--color=never` and anything else that turns colours off for the listing
+static COLOURED: AtomicBool = AtomicBool::new(true);
+
+#[derive(Debug)]
+pub enum Error {
+    /// Arguments clap itself rejected, or a request for `--help` or `--version`.
+    Usage(clap::Error),
+    /// A value from the command line or a config file that can't be used.
+    ///
+    /// `left` tells where it came from, such as `--sort-by` or `config.toml: sort_by`.
+    Argument { left: String, message: String },
+    /// A directory that couldn't be read, or an entry in it.
+    ///
+    /// `is_root` is set for the paths given on the command line, which makes it a serious error
+    /// rather than a minor one.
+    Read { path: PathBuf, source: io::Error, is_root: bool },
+    /// A followed symlink leading to a directory that was already listed, which is skipped
+    /// rather than listed twice or forever.
+    ///
+    /// Serious like `ls` reporting an already-listed directory, `target` is where `path` links to.
+    Loop { path: PathBuf, target: PathBuf },
+    /// The listing itself couldn't be written out.
+    Output(io::Error),
+}
+
+impl Error {
+    pub fn argument(left: &str, message: String) -> Self {
+        Error::Argument {
+            left: left.to_string(),
+            message,
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Usage(error) => error.exit_code(),
+            Error::Read { is_root: true, .. } | Error::Loop { .. } => SERIOUS,
+            Error::Read { is_root: false, .. } => MINOR,
+            Error::Argument { .. } | Error::Output(_) => SERIOUS,
+        }
+    }
+
+    fn left(&self) -> String {
+        match self {
+            Error::Usage(_) => String::from("usage"),
+            Error::Argument { left, .. } => left.clone(),
+            Error::Read { path, .. } | Error::Loop { path, .. } => path.display().to_string(),
+            Error::Output(_) => String::from("output"),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Usage(error) => write!(f, "{}", error),
+            Error::Argument { message, .. } => write!(f, "{}", message),
+            Error::Read { source, .. } => write!(f, "{}", source),
+            Error::Loop { target, .. } => write!(
+                f,
+                r#"Not following the link to "{}", that directory was already listed."#,
+                target.display()
+            ),
+            Error::Output(error) => write!(f, "Failed to write the listing: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Usage(error) => Some(error),
+            Error::Read { source, .. } | Error::Output(source) => Some(source),
+            Error::Argument { .. } | Error::Loop { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Output(error)
+    }
+}
+
+impl From<clap::Error> for Error {
+    fn from(error: clap::Error) -> Self {
+        Error::Usage(error)
+    }
+}
+
+/// Lets errors be coloured or not, following the colour setting resolved for the listing.
+///
+/// Even when allowed, colours are only used when stderr is a terminal and `NO_COLOR` is unset.
+pub fn set_coloured(coloured: bool) {
+    COLOURED.store(coloured, Ordering::Relaxed);
+}
+
+fn is_coloured() -> bool {
+    COLOURED.load(Ordering::Relaxed)
+        && io::stderr().is_terminal()
+        && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+}
+
+/// Prints `error` to stderr as `[where] what`, with the location in red on a terminal, and
+/// raises the exit code accordingly.
+pub fn report(error: &Error) {
+    EXIT_CODE.fetch_max(error.exit_code(), Ordering::Relaxed);
+
+    if let Error::Usage(error) = error {
+        // Clap knows best how to lay out its own messages, help included
+        let _ = error.print();
+    } else if is_coloured() {
+        eprintln!("\x1b[1;31m[{}]\x1b[0m {}", error.left(), error);
+    } else {
+        eprintln!("[{}] {}", error.left(), error);
+    }
+}
+
+/// The exit code for everything reported so far.
+pub fn exit_code() -> i32 {
+    EXIT_CODE.load(Ordering::Relaxed)
+}
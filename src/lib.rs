@@ -0,0 +1,65 @@
+//! Colourful `ls`, usable as a library.
+//!
+//! Build a [`Config`], either from scratch with `Config::default()` or from command line style
+//! arguments with [`parse_args`], [`list`] a directory into [`ColouredEntry`] values and
+//! [`render`] them with the layout the configuration asks for. Everything is written to a
+//! `io::Write` sink, which is best buffered.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+mod config_file;
+pub mod display;
+pub mod error;
+mod parser;
+mod subparsers;
+pub mod traversal;
+pub mod types;
+
+pub use display::{JsonDocument, Listing};
+pub use error::Error;
+pub use parser::{parse_args, parse_user_args};
+pub use types::{ColouredEntry, Config, Kind, OutputFormat, RgbColor};
+
+/// Loads a TOML configuration file on top of `config`.
+pub fn apply_config_file(config: &mut Config, path: &Path) -> Result<(), Error> {
+    config_file::apply(config, path, true)
+}
+
+/// Reads `path` into filtered and sorted entries, the way a listing of it would show them.
+pub fn list(config: &Config, path: &Path) -> Listing {
+    Listing::read(config, path)
+}
+
+/// Writes a listing with its title and entries, its errors are reported to stderr.
+///
+/// The listing counts as one the user asked for, so failing to read it is a serious error.
+pub fn render<W: Write>(out: &mut W, config: &Config, listing: Listing) -> Result<(), Error> {
+    display::display_path(out, config, listing, true)
+}
+
+/// Lists every path the way the `rainbow_ls` binary does, honouring recursion, trees and the
+/// output format.
+///
+/// Directories that can't be read are reported on the way, only failing to write stops the run.
+pub fn run<W: Write>(out: &mut W, config: &Config, paths: Vec<PathBuf>) -> Result<(), Error> {
+    error::set_coloured(config.colour_depth != types::ColourDepth::None);
+
+    let mut document: JsonDocument = JsonDocument::default();
+
+    // Machine-readable output lists trees as flat recursive listings
+    if config.tree && config.output_format == OutputFormat::Text {
+        for path_buf in paths {
+            display::display_tree(out, config, &path_buf)?;
+        }
+    } else if config.recursive || config.tree {
+        traversal::call_recursive(out, config, paths, &mut document)?;
+    } else {
+        traversal::call_non_recursive(out, config, paths, &mut document)?;
+    }
+
+    if config.output_format == OutputFormat::Json {
+        document.print(out)?;
+    }
+    Ok(())
+}
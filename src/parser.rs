@@ -4,8 +4,9 @@ use std::path::PathBuf;
 
 use clap::{ArgAction, Parser as ClapParser};
 
-use crate::subparsers;
-use crate::Config;
+use crate::error::{self, Error};
+use crate::types::{ColourDepth, Config};
+use crate::{config_file, subparsers};
 
 #[derive(ClapParser, Debug)]
 #[command(
@@ -20,48 +21,156 @@ struct Cli {
     /// When enabled the listing switches to a single-column layout, mirroring the behaviour of
     /// `ls -1`. This is handy when piping the output or when filenames are too wide for the
     /// column view.
-    #[arg(short = '1', long = "one-per-line", action = ArgAction::SetTrue)]
+    #[arg(short = '1', long = "one-per-line", action = ArgAction::SetTrue, overrides_with = "no_one_per_line")]
     one_per_line: bool,
-    /// Request the long-listing layout.
-    ///
-    /// Parsed for compatibility with the classic `ls -l` view. The current renderer keeps the
-    /// compact layouts, but the option is preserved for future expansion.
-    #[arg(long = "long-listing", alias = "ll", action = ArgAction::SetTrue)]
+    /// Undo `--one-per-line`, such as one set by the config file.
+    #[arg(long = "no-one-per-line", action = ArgAction::SetTrue, hide = true, overrides_with = "one_per_line")]
+    no_one_per_line: bool,
+    /// Use the long-listing layout.
+    ///
+    /// Prints one entry per line preceded by its permissions, link count, owner, group, size and
+    /// modification time, mirroring the classic `ls -l` view.
+    #[arg(long = "long-listing", alias = "ll", action = ArgAction::SetTrue, overrides_with = "no_long_listing")]
     long_listing: bool,
+    /// Undo `--long-listing`, such as one set by the config file.
+    #[arg(long = "no-long-listing", action = ArgAction::SetTrue, hide = true, overrides_with = "long_listing")]
+    no_long_listing: bool,
+    /// Draw recursive listings as a tree.
+    ///
+    /// Every directory is expanded below its own entry with box-drawing guides (`├──`, `└──`),
+    /// siblings being sorted like any other listing. Implies recursion.
+    #[arg(long = "tree", action = ArgAction::SetTrue, overrides_with = "no_tree")]
+    tree: bool,
+    /// Undo `--tree`, such as one set by the config file.
+    #[arg(long = "no-tree", action = ArgAction::SetTrue, hide = true, overrides_with = "tree")]
+    no_tree: bool,
+    /// Draw tree guides with plain ASCII characters.
+    ///
+    /// Used automatically when the locale isn't UTF-8, for terminals that can't display
+    /// box-drawing characters.
+    #[arg(long = "ascii", action = ArgAction::SetTrue, overrides_with = "no_ascii")]
+    ascii: bool,
+    /// Undo `--ascii`, such as one set by the config file.
+    #[arg(long = "no-ascii", action = ArgAction::SetTrue, hide = true, overrides_with = "ascii")]
+    no_ascii: bool,
+    /// Output format: `text`, `json` or `ndjson`.
+    ///
+    /// `json` prints a single document holding every listed directory, `ndjson` prints one record
+    /// per line. Both carry a schema `version` and describe each entry's name, path, kind,
+    /// extension, size, timestamps and colour, while unreadable entries become error records.
+    #[arg(long = "output", value_name = "FORMAT")]
+    output: Option<OsString>,
+    /// Show git status markers for entries inside a work tree.
+    ///
+    /// The long listing gains a column with the index state then the work tree state (`S`taged,
+    /// `M`odified, `?` untracked, `!` ignored, `UU` conflicted), other layouts prefix names with
+    /// the most pressing one. Directories aggregate their children. Only the local repository
+    /// is read.
+    #[arg(long = "git", action = ArgAction::SetTrue, overrides_with = "no_git")]
+    git: bool,
+    /// Undo `--git`, such as one set by the config file.
+    #[arg(long = "no-git", action = ArgAction::SetTrue, hide = true, overrides_with = "git")]
+    no_git: bool,
     /// List directories before any other entry type.
     ///
-    /// Directories are grouped ahead of regular files while the selected sort field is still used
-    /// inside each group. This matches the behaviour of `ls --group-directories-first`.
-    #[arg(long = "group-directories-first", alias = "gdf", action = ArgAction::SetTrue)]
+    /// Shorthand for a leading `directories` sort key: the selected sort keys are still used
+    /// inside each group, and `--reverse` leaves directories first. This matches the behaviour
+    /// of `ls --group-directories-first`.
+    #[arg(long = "group-directories-first", alias = "gdf", action = ArgAction::SetTrue, overrides_with = "no_group_directories_first")]
     group_directories_first: bool,
+    /// Undo `--group-directories-first`, such as one set by the config file.
+    #[arg(long = "no-group-directories-first", action = ArgAction::SetTrue, hide = true, overrides_with = "group_directories_first")]
+    no_group_directories_first: bool,
     /// Reverse the computed sort order.
     ///
-    /// Apply this after any other ordering rule so the last entry becomes the first one displayed.
-    #[arg(long = "reverse", alias = "rev", action = ArgAction::SetTrue)]
+    /// Flips the direction of every sort key and of the final tie on names, so the last entry
+    /// becomes the first one displayed. Unsorted listings are shown in reverse read order.
+    #[arg(long = "reverse", alias = "rev", action = ArgAction::SetTrue, overrides_with = "no_reverse")]
     reverse: bool,
+    /// Undo `--reverse`, such as one set by the config file.
+    #[arg(long = "no-reverse", action = ArgAction::SetTrue, hide = true, overrides_with = "reverse")]
+    no_reverse: bool,
     /// Include dot-prefixed entries in the output.
     ///
     /// Hidden files and directories (those starting with `.`) are ignored by default; this flag
     /// forces them to be listed.
-    #[arg(long = "show-dotfiles", alias = "sd", action = ArgAction::SetTrue)]
+    #[arg(long = "show-dotfiles", alias = "sd", action = ArgAction::SetTrue, overrides_with = "no_show_dotfiles")]
     show_dotfiles: bool,
+    /// Undo `--show-dotfiles`, such as one set by the config file.
+    #[arg(long = "no-show-dotfiles", action = ArgAction::SetTrue, hide = true, overrides_with = "show_dotfiles")]
+    no_show_dotfiles: bool,
     /// Include editor backup files in the listing.
     ///
     /// Files ending with `~` are skipped unless this flag is provided.
-    #[arg(long = "show-backups", alias = "sb", action = ArgAction::SetTrue)]
+    #[arg(long = "show-backups", alias = "sb", action = ArgAction::SetTrue, overrides_with = "no_show_backups")]
     show_backups: bool,
+    /// Undo `--show-backups`, such as one set by the config file.
+    #[arg(long = "no-show-backups", action = ArgAction::SetTrue, hide = true, overrides_with = "show_backups")]
+    no_show_backups: bool,
+    /// Only list symbolic links whose target doesn't exist.
+    ///
+    /// Combine with `--recursive` to hunt down links left dangling by a move or a refactor;
+    /// directories are still descended into, and kept as the skeleton of `--tree`.
+    #[arg(long = "only-broken-symlinks", action = ArgAction::SetTrue, overrides_with = "no_only_broken_symlinks")]
+    only_broken_symlinks: bool,
+    /// Undo `--only-broken-symlinks`, such as one set by the config file.
+    #[arg(long = "no-only-broken-symlinks", action = ArgAction::SetTrue, hide = true, overrides_with = "only_broken_symlinks")]
+    no_only_broken_symlinks: bool,
+    /// Show where symbolic links point to, as `name -> target`.
+    ///
+    /// Every hop of a chain of links is shown, each coloured like the entry it resolves to, and
+    /// targets that don't exist use the broken symlink style.
+    #[arg(long = "link-targets", action = ArgAction::SetTrue, overrides_with = "no_link_targets")]
+    link_targets: bool,
+    /// Undo `--link-targets`, such as one set by the config file.
+    #[arg(long = "no-link-targets", action = ArgAction::SetTrue, hide = true, overrides_with = "link_targets")]
+    no_link_targets: bool,
+    /// Append an indicator to names depending on their type, like `ls -F`.
+    ///
+    /// `/` marks directories, `*` executables, `@` symbolic links, `|` FIFOs and `=` sockets.
+    /// Suffixes set explicitly for a type are used instead.
+    #[arg(short = 'F', long = "classify", action = ArgAction::SetTrue, overrides_with = "no_classify")]
+    classify: bool,
+    /// Undo `--classify`, such as one set by the config file.
+    #[arg(long = "no-classify", action = ArgAction::SetTrue, hide = true, overrides_with = "classify")]
+    no_classify: bool,
     /// Recurse into each directory that is encountered.
     ///
-    /// Every directory is visited depth-first and printed with its own header before the entries
-    /// are shown.
-    #[arg(long = "recursive", alias = "rec", action = ArgAction::SetTrue)]
+    /// Every directory is printed with its own header before the entries are shown. Directories
+    /// are visited depth-first like `ls -R` unless `--traversal bfs` is given, and subdirectories
+    /// always come in the order they are displayed in.
+    #[arg(long = "recursive", alias = "rec", action = ArgAction::SetTrue, overrides_with = "no_recursive")]
     recursive: bool,
+    /// Undo `--recursive`, such as one set by the config file.
+    #[arg(long = "no-recursive", action = ArgAction::SetTrue, hide = true, overrides_with = "recursive")]
+    no_recursive: bool,
     /// Follow symbolic links when recursing.
     ///
     /// Symlinks are resolved and their targets enqueued when traversal is recursive. This is
     /// ignored when recursion itself is disabled.
-    #[arg(long = "follow-symlinks", alias = "fs", action = ArgAction::SetTrue)]
+    #[arg(long = "follow-symlinks", alias = "fs", action = ArgAction::SetTrue, overrides_with = "no_follow_symlinks")]
     follow_symlinks: bool,
+    /// Undo `--follow-symlinks`, such as one set by the config file.
+    #[arg(long = "no-follow-symlinks", action = ArgAction::SetTrue, hide = true, overrides_with = "follow_symlinks")]
+    no_follow_symlinks: bool,
+    /// Maximum number of levels shown below each path by `--recursive` and `--tree`.
+    ///
+    /// `1` lists the given directories without descending into any of their subdirectories.
+    #[arg(long = "max-depth", value_name = "N")]
+    max_depth: Option<OsString>,
+    /// Order in which `--recursive` visits directories: `dfs` or `bfs`.
+    ///
+    /// `dfs` (the default) lists a directory's subdirectories right after it, `bfs` lists every
+    /// directory of a level before moving on to the next one.
+    #[arg(long = "traversal", value_name = "ORDER")]
+    traversal: Option<OsString>,
+    /// Number of threads reading directories during recursive listings.
+    ///
+    /// Directories are read and stat'ed concurrently, only a few ahead of the one being printed,
+    /// but still printed in the same order as a single-threaded run. Defaults to the number of
+    /// available CPUs; `1` disables the pool.
+    #[arg(long = "threads", value_name = "N")]
+    threads: Option<OsString>,
 
     /// ANSI style codes to apply to directory headers.
     ///
@@ -81,16 +190,66 @@ struct Cli {
     /// Accepts digits (`0-9`) that are interpreted as separate SGR attributes.
     #[arg(long = "directories", value_name = "DIGITS")]
     directories: Option<OsString>,
+    /// ANSI style codes to apply to directories with the sticky bit set, like `/tmp`.
+    ///
+    /// Accepts digits (`0-9`) that are interpreted as separate SGR attributes. Sticky directories
+    /// that are also writable by others use this style too.
+    #[arg(long = "sticky-directories", value_name = "DIGITS")]
+    sticky_directories: Option<OsString>,
+    /// ANSI style codes to apply to directories anyone can write to, without the sticky bit.
+    ///
+    /// Accepts digits (`0-9`) that are interpreted as separate SGR attributes. Defaults to `7`,
+    /// reversed, since anyone may delete or replace what they hold.
+    #[arg(long = "other-writable-directories", value_name = "DIGITS")]
+    other_writable_directories: Option<OsString>,
     /// ANSI style codes to apply to executables.
     ///
     /// Accepts digits (`0-9`) that are interpreted as separate SGR attributes.
     #[arg(long = "executables", value_name = "DIGITS")]
     executables: Option<OsString>,
+    /// ANSI style codes to apply to files with the set-user-ID bit.
+    ///
+    /// Accepts digits (`0-9`) that are interpreted as separate SGR attributes. Takes precedence over
+    /// the executable style.
+    #[arg(long = "setuid", value_name = "DIGITS")]
+    setuid: Option<OsString>,
+    /// ANSI style codes to apply to files with the set-group-ID bit.
+    ///
+    /// Accepts digits (`0-9`) that are interpreted as separate SGR attributes. Takes precedence over
+    /// the executable style.
+    #[arg(long = "setgid", value_name = "DIGITS")]
+    setgid: Option<OsString>,
     /// ANSI style codes to apply to symbolic links.
     ///
     /// Accepts digits (`0-9`) that are interpreted as separate SGR attributes.
     #[arg(long = "symlinks", value_name = "DIGITS")]
     symlinks: Option<OsString>,
+    /// ANSI style codes to apply to symbolic links whose target doesn't exist.
+    ///
+    /// Accepts digits (`0-9`) that are interpreted as separate SGR attributes. Defaults to `9`,
+    /// crossed out.
+    #[arg(long = "broken-symlinks", value_name = "DIGITS")]
+    broken_symlinks: Option<OsString>,
+    /// ANSI style codes to apply to named pipes.
+    ///
+    /// Accepts digits (`0-9`) that are interpreted as separate SGR attributes.
+    #[arg(long = "fifos", value_name = "DIGITS")]
+    fifos: Option<OsString>,
+    /// ANSI style codes to apply to Unix domain sockets.
+    ///
+    /// Accepts digits (`0-9`) that are interpreted as separate SGR attributes.
+    #[arg(long = "sockets", value_name = "DIGITS")]
+    sockets: Option<OsString>,
+    /// ANSI style codes to apply to block devices.
+    ///
+    /// Accepts digits (`0-9`) that are interpreted as separate SGR attributes.
+    #[arg(long = "block-devices", value_name = "DIGITS")]
+    block_devices: Option<OsString>,
+    /// ANSI style codes to apply to character devices.
+    ///
+    /// Accepts digits (`0-9`) that are interpreted as separate SGR attributes.
+    #[arg(long = "char-devices", value_name = "DIGITS")]
+    char_devices: Option<OsString>,
     /// ANSI style codes to apply to entries that do not fit any other category.
     ///
     /// Accepts digits (`0-9`) that are interpreted as separate SGR attributes.
@@ -107,16 +266,61 @@ struct Cli {
     /// Applied before the coloured directory name, letting you add glyphs or contextual labels.
     #[arg(long = "directories-prefix", value_name = "STRING")]
     directories_prefix: Option<OsString>,
+    /// Prefix inserted before names of directories with the sticky bit set, like `/tmp`.
+    ///
+    /// Applied before the coloured name, in place of the directory prefix.
+    #[arg(long = "sticky-directories-prefix", value_name = "STRING")]
+    sticky_directories_prefix: Option<OsString>,
+    /// Prefix inserted before names of directories anyone can write to, without the sticky bit.
+    ///
+    /// Applied before the coloured name, in place of the directory prefix.
+    #[arg(long = "other-writable-directories-prefix", value_name = "STRING")]
+    other_writable_directories_prefix: Option<OsString>,
     /// Prefix inserted before executable file names.
     ///
     /// Useful for decorating binaries with an indicator or emoji.
     #[arg(long = "executables-prefix", value_name = "STRING")]
     executables_prefix: Option<OsString>,
+    /// Prefix inserted before names of files with the set-user-ID bit.
+    ///
+    /// Applied before the coloured name, in place of the executable prefix.
+    #[arg(long = "setuid-prefix", value_name = "STRING")]
+    setuid_prefix: Option<OsString>,
+    /// Prefix inserted before names of files with the set-group-ID bit.
+    ///
+    /// Applied before the coloured name, in place of the executable prefix.
+    #[arg(long = "setgid-prefix", value_name = "STRING")]
+    setgid_prefix: Option<OsString>,
     /// Prefix inserted before symbolic link names.
     ///
     /// Often used to add arrows that highlight the indirection.
     #[arg(long = "symlinks-prefix", value_name = "STRING")]
     symlinks_prefix: Option<OsString>,
+    /// Prefix inserted before names of broken symbolic links.
+    ///
+    /// Makes dangling links stand out even without colours.
+    #[arg(long = "broken-symlinks-prefix", value_name = "STRING")]
+    broken_symlinks_prefix: Option<OsString>,
+    /// Prefix inserted before names of named pipes.
+    ///
+    /// Applied before the coloured name of every FIFO.
+    #[arg(long = "fifos-prefix", value_name = "STRING")]
+    fifos_prefix: Option<OsString>,
+    /// Prefix inserted before names of Unix domain sockets.
+    ///
+    /// Applied before the coloured name of every socket.
+    #[arg(long = "sockets-prefix", value_name = "STRING")]
+    sockets_prefix: Option<OsString>,
+    /// Prefix inserted before names of block devices.
+    ///
+    /// Applied before the coloured name of every block device.
+    #[arg(long = "block-devices-prefix", value_name = "STRING")]
+    block_devices_prefix: Option<OsString>,
+    /// Prefix inserted before names of character devices.
+    ///
+    /// Applied before the coloured name of every character device.
+    #[arg(long = "char-devices-prefix", value_name = "STRING")]
+    char_devices_prefix: Option<OsString>,
     /// Prefix inserted before names of entries that fall back to the unknown style.
     ///
     /// Lets you annotate file types that do not match any other category.
@@ -133,16 +337,61 @@ struct Cli {
     /// Classic `ls` users can set this to `/` to mimic its output.
     #[arg(long = "directories-suffix", value_name = "STRING")]
     directories_suffix: Option<OsString>,
+    /// Suffix appended after names of directories with the sticky bit set, like `/tmp`.
+    ///
+    /// Applied after the coloured name, in place of the directory suffix.
+    #[arg(long = "sticky-directories-suffix", value_name = "STRING")]
+    sticky_directories_suffix: Option<OsString>,
+    /// Suffix appended after names of directories anyone can write to, without the sticky bit.
+    ///
+    /// Applied after the coloured name, in place of the directory suffix.
+    #[arg(long = "other-writable-directories-suffix", value_name = "STRING")]
+    other_writable_directories_suffix: Option<OsString>,
     /// Suffix appended after executable file names.
     ///
     /// Use this to flag binaries with punctuation or icons.
     #[arg(long = "executables-suffix", value_name = "STRING")]
     executables_suffix: Option<OsString>,
+    /// Suffix appended after names of files with the set-user-ID bit.
+    ///
+    /// Applied after the coloured name, in place of the executable suffix.
+    #[arg(long = "setuid-suffix", value_name = "STRING")]
+    setuid_suffix: Option<OsString>,
+    /// Suffix appended after names of files with the set-group-ID bit.
+    ///
+    /// Applied after the coloured name, in place of the executable suffix.
+    #[arg(long = "setgid-suffix", value_name = "STRING")]
+    setgid_suffix: Option<OsString>,
     /// Suffix appended after symbolic link names.
     ///
     /// Handy for reproducing the familiar `@` suffix.
     #[arg(long = "symlinks-suffix", value_name = "STRING")]
     symlinks_suffix: Option<OsString>,
+    /// Suffix appended after names of broken symbolic links.
+    ///
+    /// Something like `!` flags them the way `@` flags working links.
+    #[arg(long = "broken-symlinks-suffix", value_name = "STRING")]
+    broken_symlinks_suffix: Option<OsString>,
+    /// Suffix appended after names of named pipes.
+    ///
+    /// `--classify` uses `|`.
+    #[arg(long = "fifos-suffix", value_name = "STRING")]
+    fifos_suffix: Option<OsString>,
+    /// Suffix appended after names of Unix domain sockets.
+    ///
+    /// `--classify` uses `=`.
+    #[arg(long = "sockets-suffix", value_name = "STRING")]
+    sockets_suffix: Option<OsString>,
+    /// Suffix appended after names of block devices.
+    ///
+    /// Applied after the coloured name of every block device.
+    #[arg(long = "block-devices-suffix", value_name = "STRING")]
+    block_devices_suffix: Option<OsString>,
+    /// Suffix appended after names of character devices.
+    ///
+    /// Applied after the coloured name of every character device.
+    #[arg(long = "char-devices-suffix", value_name = "STRING")]
+    char_devices_suffix: Option<OsString>,
     /// Suffix appended after entries that use the unknown style.
     ///
     /// Offers a fallback for everything that doesn't match the other categories.
@@ -153,34 +402,137 @@ struct Cli {
     )]
     unknowns_suffix: Option<OsString>,
 
+    /// When to colour the output: `auto`, `always` or `never`.
+    ///
+    /// `auto` only colours when stdout is a terminal and honours `NO_COLOR`, `CLICOLOR` and
+    /// `CLICOLOR_FORCE`. The colour depth is picked from `COLORTERM` and `TERM`, and colours are
+    /// approximated on 256- and 16-colour terminals. `--color` alone means `always`.
+    #[arg(
+        long = "color",
+        visible_alias = "colour",
+        value_name = "WHEN",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "always"
+    )]
+    color: Option<OsString>,
     /// Seed value used to derive deterministic RGB colours from file names.
     ///
     /// Different seeds shuffle the palette that is generated from extensions while keeping the
     /// same colour per file type during a run.
     #[arg(long = "color-seed", value_name = "SEED")]
     color_seed: Option<OsString>,
-    /// Minimum brightness allowed for generated colours.
-    ///
-    /// Colours are brightened until the sum of their RGB components reaches this number (capped at
-    /// 765). Increasing the value makes entries easier to read on dark backgrounds.
-    #[arg(long = "sum", value_name = "SUM")]
-    minimal_sum: Option<OsString>,
+    /// Import the GNU `LS_COLORS` variable.
+    ///
+    /// Its `di`, `ex`, `ln`, `or`, `pi` and `so` indicators style the matching kinds of entries
+    /// and `*.ext` keys pin extension colours. Anything the variable does not cover keeps its
+    /// generated rainbow colour.
+    #[arg(long = "ls-colors", action = ArgAction::SetTrue, overrides_with = "no_ls_colors")]
+    ls_colors: bool,
+    /// Undo `--ls-colors`, such as one set by the config file.
+    #[arg(long = "no-ls-colors", action = ArgAction::SetTrue, hide = true, overrides_with = "ls_colors")]
+    no_ls_colors: bool,
+    /// Import colours from a `dircolors` database file.
+    ///
+    /// Reads the format printed by `dircolors --print-database` and applies it like
+    /// `--ls-colors`. Takes precedence over `--ls-colors` when both are given.
+    #[arg(long = "dircolors", value_name = "FILE")]
+    dircolors: Option<OsString>,
+    /// Pin colours and styles with a theme.
+    ///
+    /// Either the name of a bundled theme (`ferris`, `pastel`, `solarized`) or the path of a TOML
+    /// file with `[kinds]`, `[extensions]` and `[filenames]` tables mapping to `"#rrggbb"` colours
+    /// or `{ colour = "#rrggbb", style = "DIGITS" }`. Entries it doesn't list keep their
+    /// generated colour. Takes precedence over `--ls-colors`.
+    #[arg(long = "theme", value_name = "NAME|FILE")]
+    theme: Option<OsString>,
+    /// Show an icon in front of every name: `nerd`, `emoji` or `none`.
+    ///
+    /// Icons are picked from the file name (`Cargo.toml`, `Makefile`, ...), then the extension,
+    /// then the kind of the entry, and take the entry's colour. `nerd` needs a Nerd Font patched
+    /// font. `--icons` alone means `nerd`.
+    #[arg(
+        long = "icons",
+        value_name = "SET",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "nerd"
+    )]
+    icons: Option<OsString>,
+    /// OKLCH lightness range used for generated colours.
+    ///
+    /// Written `MIN-MAX` with bounds between 0 and 1, or a single value to pin it. Every extension
+    /// picks a hue and a lightness inside this range, so the default (`0.72-0.86`) stays readable
+    /// on dark backgrounds while something like `0.35-0.5` suits light ones.
+    #[arg(long = "lightness", value_name = "MIN-MAX")]
+    lightness: Option<OsString>,
     /// Custom timestamp format used for long listings.
     ///
-    /// Accepts `strftime`-style patterns and is retained for the future long-listing renderer.
+    /// Accepts `strftime`-style patterns and is applied to the modification time column of the
+    /// long listing.
     #[arg(long = "time-formatting", value_name = "FORMAT")]
     time_formatting: Option<OsString>,
     /// Measurement unit used when displaying file sizes.
     ///
-    /// Choose between `bytes` and `bits` for size columns once the long-listing view is available.
+    /// `bytes` and `bits` print exact counts, `si` and `si-bits` scale by powers of 1000 (`kB`,
+    /// `MB`), and `iec` and `iec-bits` scale by powers of 1024 (`KiB`, `MiB`).
     #[arg(long = "unit-size", value_name = "UNIT")]
     unit_size: Option<OsString>,
-    /// Field used to sort entries.
-    ///
-    /// Supported values include `name`, `size`, `extension`, `color`, `creation_date`,
-    /// `access_date`, and `modification_date`.
-    #[arg(long = "sort-by", value_name = "FIELD")]
+    /// Number of decimals kept for scaled sizes.
+    ///
+    /// Only applies to the `si` and `iec` units; defaults to 1 so sizes read like `1.4 MiB`.
+    #[arg(long = "size-precision", value_name = "DIGITS")]
+    size_precision: Option<OsString>,
+    /// String inserted between groups of thousands in sizes.
+    ///
+    /// For example `,` renders `1468006` as `1,468,006`. Sizes are not grouped by default.
+    #[arg(long = "thousands-separator", value_name = "SEP")]
+    thousands_separator: Option<OsString>,
+    /// Comma-separated keys used to sort entries, such as `kind,extension,-size,name`.
+    ///
+    /// Keys are `directories`, `kind`, `name`, `natural`, `version`, `size`, `extension`,
+    /// `color`, `hue`, `creation_date`, `access_date` and `modification_date`, each one only
+    /// breaking the ties left by the previous ones. A `-` prefix sorts that key in descending
+    /// order. Entries still tied are ordered by name. `natural` and `version` sort names with
+    /// that `--name-order`. `hue` runs through the colour wheel, then from dark to light, with
+    /// greys last. Defaults to `kind,extension,name`.
+    ///
+    /// `none` keeps the order the file system returns entries in; combined with `-1` or
+    /// `--output ndjson` entries are printed as soon as they are read, so huge directories start
+    /// showing up at once and use little memory.
+    #[arg(long = "sort-by", value_name = "KEYS", allow_hyphen_values = true)]
     sort_by: Option<OsString>,
+    /// How names compare: `bytes`, `natural` or `version`.
+    ///
+    /// `natural` compares runs of digits as numbers so `file2` comes before `file10`, `version`
+    /// is the `ls -v` order where `v1.9` comes before `v1.10`. Applies to name sorting and to the
+    /// ties left by any other sort field. Defaults to `bytes`.
+    #[arg(long = "name-order", value_name = "ORDER")]
+    name_order: Option<OsString>,
+    /// Compare names without regard to case.
+    ///
+    /// By default uppercase names sort before every lowercase one; with this flag `Makefile`
+    /// sorts among the `m`s.
+    #[arg(long = "ignore-case", action = ArgAction::SetTrue, overrides_with = "no_ignore_case")]
+    ignore_case: bool,
+    /// Undo `--ignore-case`, such as one set by the config file.
+    #[arg(long = "no-ignore-case", action = ArgAction::SetTrue, hide = true, overrides_with = "ignore_case")]
+    no_ignore_case: bool,
+    /// Unicode normalisation applied to names before comparing them: `nfc`, `nfd` or `none`.
+    ///
+    /// `nfc` makes names written with precomposed and combining accents compare equal, `nfd`
+    /// additionally sorts accented letters right after their base letter. Defaults to `none`,
+    /// which compares the raw UTF-8 bytes.
+    #[arg(long = "normalization", value_name = "FORM")]
+    normalization: Option<OsString>,
+    /// Skip leading punctuation such as `.` and `_` when comparing names.
+    ///
+    /// Dotfiles then sort among the other entries, `.bashrc` right next to `bin`.
+    #[arg(long = "ignore-leading-punctuation", action = ArgAction::SetTrue, overrides_with = "no_ignore_leading_punctuation")]
+    ignore_leading_punctuation: bool,
+    /// Undo `--ignore-leading-punctuation`, such as one set by the config file.
+    #[arg(long = "no-ignore-leading-punctuation", action = ArgAction::SetTrue, hide = true, overrides_with = "ignore_leading_punctuation")]
+    no_ignore_leading_punctuation: bool,
     /// String inserted between columns.
     ///
     /// This value is printed between entries when using the multi-column layouts.
@@ -192,16 +544,47 @@ struct Cli {
     /// calculated width.
     #[arg(long = "padding", value_name = "CHAR")]
     padding: Option<OsString>,
-    /// Regular expression used to force inclusion of specific entries.
-    ///
-    /// Only files whose names match the pattern are displayed when this option is provided.
-    #[arg(long = "include-pattern", value_name = "REGEX")]
-    include_pattern: Option<OsString>,
-    /// Regular expression used to hide entries.
-    ///
-    /// Any file whose name matches the pattern is skipped.
-    #[arg(long = "exclude-pattern", value_name = "REGEX")]
-    exclude_pattern: Option<OsString>,
+    /// Regular expression used to force inclusion of specific entries, can be repeated.
+    ///
+    /// Only entries matching at least one `--include-pattern` or `--include` are displayed when
+    /// any is provided. Recursive listings still descend into the directories this hides. A
+    /// pattern containing a `/` matches the path relative to the current directory, as shown in
+    /// directory titles, anywhere else it matches the name.
+    #[arg(long = "include-pattern", value_name = "REGEX", action = ArgAction::Append)]
+    include_pattern: Vec<OsString>,
+    /// Regular expression used to hide entries, can be repeated.
+    ///
+    /// Any entry matching one of the exclude patterns is skipped, even when it is also included,
+    /// and excluded directories are not descended into. Paths are matched like for
+    /// `--include-pattern`.
+    #[arg(long = "exclude-pattern", value_name = "REGEX", action = ArgAction::Append)]
+    exclude_pattern: Vec<OsString>,
+    /// Shell-style glob of entries to include, can be repeated.
+    ///
+    /// Works like `--include-pattern` but the glob has to match the whole name or path. `*` and
+    /// `?` don't cross a `/`, `**` does and `[...]` matches a class, `[!...]` its complement.
+    #[arg(long = "include", value_name = "GLOB", action = ArgAction::Append)]
+    include: Vec<OsString>,
+    /// Shell-style glob of entries to hide, can be repeated.
+    ///
+    /// Works like `--exclude-pattern`, with globs written as for `--include`.
+    #[arg(long = "exclude", alias = "ignore", value_name = "GLOB", action = ArgAction::Append)]
+    exclude: Vec<OsString>,
+    /// Match include and exclude patterns without regard to case.
+    #[arg(long = "ignore-pattern-case", action = ArgAction::SetTrue, overrides_with = "no_ignore_pattern_case")]
+    ignore_pattern_case: bool,
+    /// Undo `--ignore-pattern-case`, such as one set by the config file.
+    #[arg(long = "no-ignore-pattern-case", action = ArgAction::SetTrue, hide = true, overrides_with = "ignore_pattern_case")]
+    no_ignore_pattern_case: bool,
+    /// Read settings from this file instead of the default location.
+    ///
+    /// By default `$XDG_CONFIG_HOME/rainbow_ls/config.toml` is loaded when it exists. Keys are
+    /// named after the settings they control (`sort_by`, `show_dotfiles`, `[suffix]`, ...) and
+    /// any flag given on the command line takes precedence over the file. Every on/off flag has a
+    /// `--no-` counterpart, `--no-show-dotfiles` for instance, to turn off what the file turned
+    /// on; when both are given the last one wins.
+    #[arg(long = "config", value_name = "FILE")]
+    config: Option<PathBuf>,
     /// Manually override the detected terminal width.
     ///
     /// Supplying a width in columns affects the heuristics that pick one-line versus multi-column
@@ -216,10 +599,14 @@ struct Cli {
     paths: Vec<PathBuf>,
 }
 
-fn normalize_args() -> Vec<OsString> {
+fn normalize_args<I, T>(args: I) -> Vec<OsString>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString>,
+{
     let mut normalized: Vec<OsString> = Vec::new();
 
-    for (index, argument) in env::args_os().enumerate() {
+    for (index, argument) in args.into_iter().map(Into::into).enumerate() {
         if index == 0 {
             normalized.push(argument);
             continue;
@@ -243,37 +630,149 @@ fn normalize_args() -> Vec<OsString> {
     normalized
 }
 
-pub fn parse_user_args() -> (Config, Vec<PathBuf>) {
-    let cli = Cli::parse_from(normalize_args());
+/// Parses the arguments the program was started with.
+pub fn parse_user_args() -> Result<(Config, Vec<PathBuf>), Error> {
+    parse_args(env::args_os())
+}
+
+/// Builds a `Config` and the paths to list from command line style arguments, the first one
+/// being the program name. The configuration file is applied first, like for the binary.
+///
+/// `--help` and `--version` come back as an `Error::Usage` too, reporting it prints them.
+pub fn parse_args<I, T>(args: I) -> Result<(Config, Vec<PathBuf>), Error>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString>,
+{
+    let cli = Cli::try_parse_from(normalize_args(args))?;
 
     let mut config: Config = Config::default();
 
-    config.one_per_line = cli.one_per_line;
-    config.is_long_listing = cli.long_listing;
-    config.group_directories_first = cli.group_directories_first;
-    config.reverse = cli.reverse;
-    config.show_dotfiles = cli.show_dotfiles;
-    config.show_backups = cli.show_backups;
-    config.recursive = cli.recursive;
-    config.follow_symlinks = cli.follow_symlinks;
+    // Precedence: built-in defaults, then the config file, then the command line
+    if let Some(config_path) = cli.config {
+        config_file::apply(&mut config, &config_path, true)?;
+    } else if let Some(config_path) = config_file::default_path() {
+        config_file::apply(&mut config, &config_path, false)?;
+    }
+
+    if cli.one_per_line {
+        config.one_per_line = true;
+    } else if cli.no_one_per_line {
+        config.one_per_line = false;
+    }
+    if cli.long_listing {
+        config.is_long_listing = true;
+    } else if cli.no_long_listing {
+        config.is_long_listing = false;
+    }
+    if cli.tree {
+        config.tree = true;
+    } else if cli.no_tree {
+        config.tree = false;
+    }
+    if cli.ascii {
+        config.ascii_guides = true;
+    } else if cli.no_ascii {
+        config.ascii_guides = false;
+    }
+    if let Some(output) = cli.output {
+        config.output_format = subparsers::output_format("--output", output)?;
+    }
+    if cli.git {
+        config.git_status = true;
+    } else if cli.no_git {
+        config.git_status = false;
+    }
+    if cli.group_directories_first {
+        config.group_directories_first = true;
+    } else if cli.no_group_directories_first {
+        config.group_directories_first = false;
+    }
+    if cli.reverse {
+        config.reverse = true;
+    } else if cli.no_reverse {
+        config.reverse = false;
+    }
+    if cli.show_dotfiles {
+        config.show_dotfiles = true;
+    } else if cli.no_show_dotfiles {
+        config.show_dotfiles = false;
+    }
+    if cli.show_backups {
+        config.show_backups = true;
+    } else if cli.no_show_backups {
+        config.show_backups = false;
+    }
+    if cli.only_broken_symlinks {
+        config.only_broken_symlinks = true;
+    } else if cli.no_only_broken_symlinks {
+        config.only_broken_symlinks = false;
+    }
+    if cli.link_targets {
+        config.link_targets = true;
+    } else if cli.no_link_targets {
+        config.link_targets = false;
+    }
+    if cli.classify {
+        config.classify = true;
+    } else if cli.no_classify {
+        config.classify = false;
+    }
+    if cli.recursive {
+        config.recursive = true;
+    } else if cli.no_recursive {
+        config.recursive = false;
+    }
+    if cli.follow_symlinks {
+        config.follow_symlinks = true;
+    } else if cli.no_follow_symlinks {
+        config.follow_symlinks = false;
+    }
 
     if let Some(titles) = cli.titles {
-        config.titles = subparsers::formatting_args("--titles", titles);
+        config.titles = subparsers::formatting_args("--titles", titles)?;
     }
     if let Some(files) = cli.files {
-        config.files = subparsers::formatting_args("--files", files);
+        config.files = subparsers::formatting_args("--files", files)?;
     }
     if let Some(directories) = cli.directories {
-        config.directories = subparsers::formatting_args("--directories", directories);
+        config.directories = subparsers::formatting_args("--directories", directories)?;
+    }
+    if let Some(sticky_directories) = cli.sticky_directories {
+        config.sticky_directories = subparsers::formatting_args("--sticky-directories", sticky_directories)?;
+    }
+    if let Some(other_writable_directories) = cli.other_writable_directories {
+        config.other_writable_directories = subparsers::formatting_args("--other-writable-directories", other_writable_directories)?;
     }
     if let Some(executables) = cli.executables {
-        config.executables = subparsers::formatting_args("--executables", executables);
+        config.executables = subparsers::formatting_args("--executables", executables)?;
+    }
+    if let Some(setuid) = cli.setuid {
+        config.setuid = subparsers::formatting_args("--setuid", setuid)?;
+    }
+    if let Some(setgid) = cli.setgid {
+        config.setgid = subparsers::formatting_args("--setgid", setgid)?;
     }
     if let Some(symlinks) = cli.symlinks {
-        config.symlinks = subparsers::formatting_args("--symlinks", symlinks);
+        config.symlinks = subparsers::formatting_args("--symlinks", symlinks)?;
+    }
+    if let Some(broken_symlinks) = cli.broken_symlinks {
+        config.broken_symlinks = subparsers::formatting_args("--broken-symlinks", broken_symlinks)?;
+    }
+    if let Some(fifos) = cli.fifos {
+        config.fifos = subparsers::formatting_args("--fifos", fifos)?;
+    }
+    if let Some(sockets) = cli.sockets {
+        config.sockets = subparsers::formatting_args("--sockets", sockets)?;
+    }
+    if let Some(block_devices) = cli.block_devices {
+        config.block_devices = subparsers::formatting_args("--block-devices", block_devices)?;
+    }
+    if let Some(char_devices) = cli.char_devices {
+        config.char_devices = subparsers::formatting_args("--char-devices", char_devices)?;
     }
     if let Some(unknowns) = cli.unknowns {
-        config.unknowns = subparsers::formatting_args("--unknown", unknowns);
+        config.unknowns = subparsers::formatting_args("--unknown", unknowns)?;
     }
 
     if let Some(files_prefix) = cli.files_prefix {
@@ -282,12 +781,39 @@ pub fn parse_user_args() -> (Config, Vec<PathBuf>) {
     if let Some(directories_prefix) = cli.directories_prefix {
         config.prefix.directories = Some(directories_prefix);
     }
+    if let Some(sticky_directories_prefix) = cli.sticky_directories_prefix {
+        config.prefix.sticky_directories = Some(sticky_directories_prefix);
+    }
+    if let Some(other_writable_directories_prefix) = cli.other_writable_directories_prefix {
+        config.prefix.other_writable_directories = Some(other_writable_directories_prefix);
+    }
     if let Some(executables_prefix) = cli.executables_prefix {
         config.prefix.executables = Some(executables_prefix);
     }
+    if let Some(setuid_prefix) = cli.setuid_prefix {
+        config.prefix.setuid = Some(setuid_prefix);
+    }
+    if let Some(setgid_prefix) = cli.setgid_prefix {
+        config.prefix.setgid = Some(setgid_prefix);
+    }
     if let Some(symlinks_prefix) = cli.symlinks_prefix {
         config.prefix.symlinks = Some(symlinks_prefix);
     }
+    if let Some(broken_symlinks_prefix) = cli.broken_symlinks_prefix {
+        config.prefix.broken_symlinks = Some(broken_symlinks_prefix);
+    }
+    if let Some(fifos_prefix) = cli.fifos_prefix {
+        config.prefix.fifos = Some(fifos_prefix);
+    }
+    if let Some(sockets_prefix) = cli.sockets_prefix {
+        config.prefix.sockets = Some(sockets_prefix);
+    }
+    if let Some(block_devices_prefix) = cli.block_devices_prefix {
+        config.prefix.block_devices = Some(block_devices_prefix);
+    }
+    if let Some(char_devices_prefix) = cli.char_devices_prefix {
+        config.prefix.char_devices = Some(char_devices_prefix);
+    }
     if let Some(unknowns_prefix) = cli.unknowns_prefix {
         config.prefix.unknowns = Some(unknowns_prefix);
     }
@@ -298,48 +824,136 @@ pub fn parse_user_args() -> (Config, Vec<PathBuf>) {
     if let Some(directories_suffix) = cli.directories_suffix {
         config.suffix.directories = Some(directories_suffix);
     }
+    if let Some(sticky_directories_suffix) = cli.sticky_directories_suffix {
+        config.suffix.sticky_directories = Some(sticky_directories_suffix);
+    }
+    if let Some(other_writable_directories_suffix) = cli.other_writable_directories_suffix {
+        config.suffix.other_writable_directories = Some(other_writable_directories_suffix);
+    }
     if let Some(executables_suffix) = cli.executables_suffix {
         config.suffix.directories = Some(executables_suffix);
     }
+    if let Some(setuid_suffix) = cli.setuid_suffix {
+        config.suffix.setuid = Some(setuid_suffix);
+    }
+    if let Some(setgid_suffix) = cli.setgid_suffix {
+        config.suffix.setgid = Some(setgid_suffix);
+    }
     if let Some(symlinks_suffix) = cli.symlinks_suffix {
         config.suffix.symlinks = Some(symlinks_suffix);
     }
+    if let Some(broken_symlinks_suffix) = cli.broken_symlinks_suffix {
+        config.suffix.broken_symlinks = Some(broken_symlinks_suffix);
+    }
+    if let Some(fifos_suffix) = cli.fifos_suffix {
+        config.suffix.fifos = Some(fifos_suffix);
+    }
+    if let Some(sockets_suffix) = cli.sockets_suffix {
+        config.suffix.sockets = Some(sockets_suffix);
+    }
+    if let Some(block_devices_suffix) = cli.block_devices_suffix {
+        config.suffix.block_devices = Some(block_devices_suffix);
+    }
+    if let Some(char_devices_suffix) = cli.char_devices_suffix {
+        config.suffix.char_devices = Some(char_devices_suffix);
+    }
     if let Some(unknowns_suffix) = cli.unknowns_suffix {
         config.suffix.unknowns = Some(unknowns_suffix);
     }
 
+    if let Some(color) = cli.color {
+        config.colour_depth = ColourDepth::detect(subparsers::colour_when("--color", color)?);
+    }
+    // Whatever is wrong with the remaining arguments is already reported the way colours were set
+    error::set_coloured(config.colour_depth != ColourDepth::None);
     if let Some(color_seed) = cli.color_seed {
-        config.color_seed = subparsers::color_seed(color_seed);
+        config.color_seed = subparsers::color_seed("--color-seed", color_seed)?;
+    }
+    if let Some(dircolors) = cli.dircolors {
+        config.ls_colors = Some(subparsers::dircolors("--dircolors", dircolors)?);
+    } else if cli.ls_colors {
+        config.ls_colors = Some(subparsers::ls_colors_env());
+    } else if cli.no_ls_colors {
+        config.ls_colors = None;
+    }
+    if let Some(theme) = cli.theme {
+        config.theme = Some(subparsers::theme("--theme", theme)?);
     }
-    if let Some(minimal_sum) = cli.minimal_sum {
-        config.minimal_rgb_sum = subparsers::minimal_rgb_sum(minimal_sum);
+    if let Some(icons) = cli.icons {
+        config.icons = subparsers::icon_set("--icons", icons)?;
+    }
+    if let Some(lightness) = cli.lightness {
+        config.lightness_range = subparsers::lightness_range("--lightness", lightness)?;
     }
     if let Some(time_formatting) = cli.time_formatting {
         config.time_formatting = time_formatting;
     }
     if let Some(unit_size) = cli.unit_size {
-        config.unit_size = subparsers::unit_size(unit_size);
+        config.unit_size = subparsers::unit_size("--unit-size", unit_size)?;
+    }
+    if let Some(size_precision) = cli.size_precision {
+        config.size_precision = subparsers::size_precision("--size-precision", size_precision)?;
+    }
+    if let Some(thousands_separator) = cli.thousands_separator {
+        config.thousands_separator = Some(thousands_separator);
     }
     if let Some(sort_by) = cli.sort_by {
-        config.sort_by = subparsers::sort_by(sort_by);
+        config.sort_by = subparsers::sort_by("--sort-by", sort_by)?;
+    }
+    if let Some(name_order) = cli.name_order {
+        config.collation.name_order = subparsers::name_order("--name-order", name_order)?;
+    }
+    if cli.ignore_case {
+        config.collation.ignore_case = true;
+    } else if cli.no_ignore_case {
+        config.collation.ignore_case = false;
+    }
+    if let Some(normalization) = cli.normalization {
+        config.collation.normalization = subparsers::normalization("--normalization", normalization)?;
+    }
+    if cli.ignore_leading_punctuation {
+        config.collation.ignore_leading_punctuation = true;
+    } else if cli.no_ignore_leading_punctuation {
+        config.collation.ignore_leading_punctuation = false;
     }
     if let Some(separator) = cli.separator {
         config.separator = separator;
     }
     if let Some(padding) = cli.padding {
-        config.padding = subparsers::padding(padding);
+        config.padding = subparsers::padding("--padding", padding)?;
+    }
+    // Added to the patterns of the config file rather than replacing them
+    for include_pattern in cli.include_pattern {
+        config.include_patterns.push(subparsers::regex_patterns("--include-pattern", include_pattern)?);
     }
-    if let Some(include_pattern) = cli.include_pattern {
-        config.include_pattern = subparsers::regex_patterns("--include-pattern", include_pattern);
+    for exclude_pattern in cli.exclude_pattern {
+        config.exclude_patterns.push(subparsers::regex_patterns("--exclude-pattern", exclude_pattern)?);
     }
-    if let Some(exclude_pattern) = cli.exclude_pattern {
-        config.exclude_pattern = subparsers::regex_patterns("--exclude-pattern", exclude_pattern);
+    for include in cli.include {
+        config.include_patterns.push(subparsers::glob_patterns("--include", include)?);
+    }
+    for exclude in cli.exclude {
+        config.exclude_patterns.push(subparsers::glob_patterns("--exclude", exclude)?);
+    }
+    if cli.ignore_pattern_case {
+        config.ignore_pattern_case = true;
+    } else if cli.no_ignore_pattern_case {
+        config.ignore_pattern_case = false;
     }
     if let Some(width) = cli.width {
-        config.term_width = subparsers::width(width);
+        config.term_width = subparsers::width("--width", width)?;
+    }
+    if let Some(max_depth) = cli.max_depth {
+        config.max_depth = Some(subparsers::max_depth("--max-depth", max_depth)?);
+    }
+    if let Some(traversal) = cli.traversal {
+        config.traversal = subparsers::traversal("--traversal", traversal)?;
+    }
+    if let Some(threads) = cli.threads {
+        config.threads = subparsers::threads("--threads", threads)?;
     }
 
-    let paths = subparsers::default_to_curr_dir(cli.paths);
+    let paths = subparsers::default_to_curr_dir(cli.paths)?;
 
-    (config, paths)
+    Ok((config, paths))
 }
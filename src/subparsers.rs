@@ -1,169 +1,403 @@
 use std::borrow::Cow;
 use std::env;
 use std::ffi::OsString;
+use std::fs;
+use std::io;
 use std::path::PathBuf;
-use std::process;
 
-use regex::Regex;
 use unicode_segmentation::UnicodeSegmentation;
 
-use crate::types::{SortingReference, SizeMeasurementUnit};
-
-/// TODO: Centralise everything so the err message is shown in red
+use crate::error::{self, Error};
+use crate::types::{ColourWhen, IconSet, LsColors, NameOrder, Normalization, OutputFormat, Pattern, SortKey, SortingReference, SizeMeasurementUnit, Theme, Traversal};
 
 // Kwargs
 
-fn handle_digit(mut ret: Vec<u8>, digit: u32, left: &str, chr: char) -> Vec<u8> {
+fn handle_digit(mut ret: Vec<u8>, digit: u32, left: &str, chr: char) -> Result<Vec<u8>, Error> {
     if (0..=9).contains(&digit) {
         ret.push(digit as u8);
     } else {
-        eprintln!(r#"[{}] Expected a digit between 0 and 9 inclusive, got: {}."#, left, chr);
-        process::exit(1);
+        return Err(Error::argument(
+            left,
+            format!(r#"Expected a digit between 0 and 9 inclusive, got: {}."#, chr),
+        ));
     }
-    ret
+    Ok(ret)
 }
 
-pub fn formatting_args(left: &str, right: OsString) -> Vec<u8> {
+pub fn formatting_args(left: &str, right: OsString) -> Result<Vec<u8>, Error> {
     let mut ret: Vec<u8> = Vec::new();
 
     for chr in right.to_string_lossy().chars() {
         if let Some(digit) = chr.to_digit(10) {
-            ret = handle_digit(ret, digit, left, chr);
+            ret = handle_digit(ret, digit, left, chr)?;
         } else {
-            eprintln!(r#"[{}] Failed to convert "{}" to a digit."#, left, chr);
-            process::exit(1);
+            return Err(Error::argument(left, format!(r#"Failed to convert "{}" to a digit."#, chr)));
         }
     }
 
-    ret
+    Ok(ret)
 }
 
-fn check_sum_bounds(sum: usize) -> usize {
-    if sum < 765 {
-        sum
-    } else {
-        eprintln!(r#"The provided minimal is too sum, max: 765, got: "{}"."#, sum);
-        process::exit(1);
+pub fn colour_when(left: &str, right: OsString) -> Result<ColourWhen, Error> {
+    let lossy_right: &str = &right.to_string_lossy();
+
+    match lossy_right.to_lowercase().as_str() {
+        "always" | "yes" | "force" => Ok(ColourWhen::Always),
+        "auto" | "tty" | "if-tty" => Ok(ColourWhen::Auto),
+        "never" | "no" | "none" => Ok(ColourWhen::Never),
+        _ => Err(Error::argument(
+            left,
+            format!(r#"Failed to convert "{}" to a valid mode (auto/always/never)."#, lossy_right),
+        )),
+    }
+}
+
+pub fn output_format(left: &str, right: OsString) -> Result<OutputFormat, Error> {
+    let lossy_right: &str = &right.to_string_lossy();
+
+    match lossy_right.to_lowercase().as_str() {
+        "text" => Ok(OutputFormat::Text),
+        "json" => Ok(OutputFormat::Json),
+        "ndjson" | "jsonl" => Ok(OutputFormat::Ndjson),
+        _ => Err(Error::argument(
+            left,
+            format!(r#"Failed to convert "{}" to a valid output format (text/json/ndjson)."#, lossy_right),
+        )),
     }
 }
 
-pub fn color_seed(right: OsString) -> usize {
+pub fn color_seed(left: &str, right: OsString) -> Result<usize, Error> {
     let lossy_right: Cow<str> = right.to_string_lossy();
 
     if let Ok(seed) = lossy_right.parse::<usize>() {
-        seed
+        Ok(seed)
     } else {
-        eprintln!("Failed to convert {} to a valid color seed", lossy_right);
-        process::exit(1);
+        Err(Error::argument(left, format!("Failed to convert {} to a valid color seed.", lossy_right)))
     }
 }
 
-pub fn minimal_rgb_sum(right: OsString) -> usize {
+fn parse_lightness(bound: &str) -> Option<f64> {
+    match bound.trim().parse::<f64>() {
+        Ok(lightness) if (0.0..=1.0).contains(&lightness) => Some(lightness),
+        _ => None,
+    }
+}
+
+pub fn lightness_range(left: &str, right: OsString) -> Result<(f64, f64), Error> {
     let lossy_right: &str = &right.to_string_lossy();
 
-    if let Ok(sum) = lossy_right.parse::<usize>() {
-        check_sum_bounds(sum)
-    } else {
-        eprintln!(r#"Failed to parse the minimal sum as a positive number, got: "{}"."#, lossy_right);
-        process::exit(1);
+    let bounds: Option<(f64, f64)> = match lossy_right.split_once('-') {
+        Some((min, max)) => parse_lightness(min).zip(parse_lightness(max)),
+        None => parse_lightness(lossy_right).map(|lightness| (lightness, lightness)),
+    };
+
+    match bounds {
+        Some((min, max)) if min <= max => Ok((min, max)),
+        _ => Err(Error::argument(
+            left,
+            format!(
+                r#"Expected a lightness range such as "0.7-0.85" with bounds between 0 and 1, got: "{}"."#,
+                lossy_right
+            ),
+        )),
     }
 }
 
-pub fn unit_size(right: OsString) -> SizeMeasurementUnit {
+pub fn unit_size(left: &str, right: OsString) -> Result<SizeMeasurementUnit, Error> {
     let lossy_right: &str = &right.to_string_lossy();
 
     match lossy_right.to_lowercase().as_str() {
-        "bytes" => SizeMeasurementUnit::Bytes,
-        "bits" => SizeMeasurementUnit::Bits,
-        _ => {
-            eprintln!(r#"Failed to convert "{}" to a valid size unit (bytes/bits)."#, lossy_right);
-            process::exit(1);
-        },
+        "bytes" | "raw" => Ok(SizeMeasurementUnit::Bytes),
+        "bits" => Ok(SizeMeasurementUnit::Bits),
+        "si" | "si-bytes" => Ok(SizeMeasurementUnit::SiBytes),
+        "si-bits" => Ok(SizeMeasurementUnit::SiBits),
+        "iec" | "iec-bytes" => Ok(SizeMeasurementUnit::IecBytes),
+        "iec-bits" => Ok(SizeMeasurementUnit::IecBits),
+        _ => Err(Error::argument(
+            left,
+            format!(
+                r#"Failed to convert "{}" to a valid size unit (bytes/bits/si/si-bits/iec/iec-bits)."#,
+                lossy_right
+            ),
+        )),
     }
 }
 
-pub fn padding(right: OsString) -> OsString {
+pub fn size_precision(left: &str, right: OsString) -> Result<usize, Error> {
+    let lossy_right: &str = &right.to_string_lossy();
+
+    match lossy_right.parse::<usize>() {
+        Ok(precision) if precision <= 9 => Ok(precision),
+        _ => Err(Error::argument(
+            left,
+            format!(r#"Expected a size precision between 0 and 9 inclusive, got: "{}"."#, lossy_right),
+        )),
+    }
+}
+
+pub fn padding(left: &str, right: OsString) -> Result<OsString, Error> {
 
     let lossy_right: &str = &right.to_string_lossy();
 
     if lossy_right.grapheme_indices(true).count() == 1 {
-        right
+        Ok(right)
     } else {
-        eprintln!(r#"Failed to parse "{}" as a valid padding char."#, lossy_right);
-        process::exit(1);
+        Err(Error::argument(left, format!(r#"Failed to parse "{}" as a valid padding char."#, lossy_right)))
+    }
+}
+
+fn sort_field(left: &str, name: &str) -> Result<SortingReference, Error> {
+    match name.to_lowercase().as_str() {
+        "directories" | "dirs" => Ok(SortingReference::Directories),
+        "kind" | "type" => Ok(SortingReference::Kind),
+        "name" => Ok(SortingReference::Name),
+        "natural" => Ok(SortingReference::Natural),
+        "version" => Ok(SortingReference::Version),
+        "size" => Ok(SortingReference::Size),
+        "extension" => Ok(SortingReference::Extension),
+        "creation_date" | "creationdate" => Ok(SortingReference::CreationDate),
+        "access_date" | "accessdate" | "accesdate" => Ok(SortingReference::AccessDate),
+        "modification_date" | "modificationdate" => Ok(SortingReference::ModificationDate),
+        "color" | "colour" => Ok(SortingReference::Colour),
+        "hue" | "rainbow" => Ok(SortingReference::Hue),
+        _ => Err(Error::argument(
+            left,
+            format!(
+                "Unrecognized sort type: \"{}\" not contained in: \
+                [directories, kind, name, natural, version, size, extension, color / colour, hue, \
+                creation_date / CreationDate, access_date / AccessDate, \
+                modification_date / ModificationDate, none / unsorted].",
+                name
+            ),
+        )),
     }
 }
 
-pub fn sort_by(right: OsString) -> SortingReference {
+/// Parses a comma-separated list of sort keys, each one descending when prefixed with `-`.
+pub fn sort_by(left: &str, right: OsString) -> Result<Vec<SortKey>, Error> {
     let lossy_right: &str = &right.to_string_lossy();
 
-    match lossy_right.to_lowercase().as_str() {
-        "name" => SortingReference::Name,
-        "size" => SortingReference::Size,
-        "extension" => SortingReference::Extension,
-        "creation_date" | "creationdate" => SortingReference::CreationDate,
-        "access_date" | "accesdate" => SortingReference::AccessDate,
-        "modification_date" | "ModificationDate" => SortingReference::ModificationDate,
-        "color" | "colour" => SortingReference::Colour,
-        _ => {
-            eprintln!(
-                r#"Unrecognized sort type: "{}" not contained in: \
-                [name, size, extension, color / colour, creation_date / CreationDate, \
-                access_date / AccessDate, modification_date / ModificationDate].
-                "#, lossy_right);
-                
-            process::exit(1);
+    if ["none", "unsorted"].contains(&lossy_right.trim().to_lowercase().as_str()) {
+        return Ok(Vec::new());
+    }
+
+    let mut sort_keys: Vec<SortKey> = Vec::new();
+
+    for item in lossy_right.split(',').map(str::trim) {
+        let (name, descending): (&str, bool) = match item.strip_prefix('-') {
+            Some(name) => (name, true),
+            None => (item.strip_prefix('+').unwrap_or(item), false),
+        };
+
+        if name.is_empty() {
+            return Err(Error::argument(
+                left,
+                format!(r#"Expected a comma-separated list of sort keys such as "kind,-size,name", got: "{}"."#, lossy_right),
+            ));
         }
+
+        sort_keys.push(SortKey {
+            field: sort_field(left, name)?,
+            descending,
+        });
+    }
+
+    Ok(sort_keys)
+}
+
+pub fn name_order(left: &str, right: OsString) -> Result<NameOrder, Error> {
+    let lossy_right: &str = &right.to_string_lossy();
+
+    match lossy_right.to_lowercase().as_str() {
+        "bytes" | "raw" => Ok(NameOrder::Bytes),
+        "natural" => Ok(NameOrder::Natural),
+        "version" => Ok(NameOrder::Version),
+        _ => Err(Error::argument(
+            left,
+            format!(r#"Failed to convert "{}" to a valid name order (bytes/natural/version)."#, lossy_right),
+        )),
     }
 }
 
-pub fn regex_patterns(left: &str, right: OsString) -> Option<Regex> {
+pub fn normalization(left: &str, right: OsString) -> Result<Normalization, Error> {
     let lossy_right: &str = &right.to_string_lossy();
 
-    let regex = Regex::new(lossy_right);
+    match lossy_right.to_lowercase().as_str() {
+        "nfc" => Ok(Normalization::Nfc),
+        "nfd" => Ok(Normalization::Nfd),
+        "none" => Ok(Normalization::None),
+        _ => Err(Error::argument(
+            left,
+            format!(r#"Failed to convert "{}" to a valid normalization form (nfc/nfd/none)."#, lossy_right),
+        )),
+    }
+}
 
-    match regex {
-        Ok(re) => Some(re),
-        Err(error) => {
-            eprintln!(r#"[{}] Failed to compile "{}" into a valid regex."#, left, lossy_right);
-            eprintln!(r#"Error: "{}""#, error);
-            process::exit(1);
-        }
+pub fn ls_colors_env() -> LsColors {
+    match env::var("LS_COLORS") {
+        Ok(spec) => LsColors::parse(&spec),
+        Err(_) => LsColors::default(),
+    }
+}
+
+pub fn dircolors(left: &str, right: OsString) -> Result<LsColors, Error> {
+    match fs::read_to_string(&right) {
+        Ok(contents) => Ok(LsColors::from_dircolors(&contents)),
+        Err(error) => Err(Error::argument(
+            left,
+            format!(r#"Failed to read the dircolors database "{}": {}"#, right.to_string_lossy(), error),
+        )),
     }
 }
 
-pub fn width(right: OsString) -> Option<usize> {
+pub fn theme(left: &str, right: OsString) -> Result<Theme, Error> {
+    let lossy_right: &str = &right.to_string_lossy();
+
+    let contents: String = match Theme::bundled(lossy_right) {
+        Some(contents) => contents.to_string(),
+        None => match fs::read_to_string(&right) {
+            Ok(contents) => contents,
+            Err(error) => {
+                return Err(Error::argument(
+                    left,
+                    format!(
+                        r#""{}" is neither a bundled theme ({}) nor a readable file: {}"#,
+                        lossy_right,
+                        Theme::bundled_names().join(", "),
+                        error
+                    ),
+                ));
+            }
+        },
+    };
+
+    match Theme::parse(&contents) {
+        Ok(theme) => Ok(theme),
+        Err(error) => Err(Error::argument(
+            left,
+            format!(r#"Failed to load the theme "{}": {}"#, lossy_right, error),
+        )),
+    }
+}
+
+pub fn icon_set(left: &str, right: OsString) -> Result<IconSet, Error> {
+    let lossy_right: &str = &right.to_string_lossy();
+
+    match lossy_right.to_lowercase().as_str() {
+        "nerd" | "nerd-font" | "nerdfont" => Ok(IconSet::Nerd),
+        "emoji" => Ok(IconSet::Emoji),
+        "none" | "never" | "no" => Ok(IconSet::None),
+        _ => Err(Error::argument(
+            left,
+            format!(r#"Failed to convert "{}" to a valid icon set (nerd/emoji/none)."#, lossy_right),
+        )),
+    }
+}
+
+pub fn regex_patterns(left: &str, right: OsString) -> Result<Pattern, Error> {
+    let lossy_right: &str = &right.to_string_lossy();
+
+    match Pattern::regex(lossy_right) {
+        Ok(pattern) => Ok(pattern),
+        Err(error) => Err(Error::argument(
+            left,
+            format!(r#"Failed to compile "{}" into a valid regex: {}"#, lossy_right, error),
+        )),
+    }
+}
+
+pub fn glob_patterns(left: &str, right: OsString) -> Result<Pattern, Error> {
+    let lossy_right: &str = &right.to_string_lossy();
+
+    match Pattern::glob(lossy_right) {
+        Ok(pattern) => Ok(pattern),
+        Err(error) => Err(Error::argument(
+            left,
+            format!(r#"Failed to compile "{}" into a valid glob: {}"#, lossy_right, error),
+        )),
+    }
+}
+
+pub fn width(left: &str, right: OsString) -> Result<Option<usize>, Error> {
     let lossy_right = right.to_string_lossy();
     if let Ok(w) = lossy_right.parse::<usize>() {
-        Some(w)
+        Ok(Some(w))
     } else {
-        eprintln!(r#"Failed to convert "{}" to a valid width."#, lossy_right);
-        process::exit(1);
+        Err(Error::argument(left, format!(r#"Failed to convert "{}" to a valid width."#, lossy_right)))
     }
 
 }
 
-pub fn default_to_curr_dir(mut paths: Vec<PathBuf>) -> Vec<PathBuf> {
-    let curr_exe: &PathBuf = &env::current_exe().unwrap_or_else(|_| PathBuf::new());
+pub fn max_depth(left: &str, right: OsString) -> Result<usize, Error> {
+    let lossy_right: Cow<str> = right.to_string_lossy();
 
-    for path_buf in paths.iter() {
+    match lossy_right.parse::<usize>() {
+        Ok(max_depth) if max_depth > 0 => Ok(max_depth),
+        _ => Err(Error::argument(
+            left,
+            format!(r#"Failed to convert "{}" to a valid depth (1 or more)."#, lossy_right),
+        )),
+    }
+}
 
-        if path_buf == curr_exe {
-            continue;
-        }
+pub fn traversal(left: &str, right: OsString) -> Result<Traversal, Error> {
+    let lossy_right: &str = &right.to_string_lossy();
 
-        if path_buf.is_dir() {
-            paths.sort_unstable();
-            return paths;
-        }
+    match lossy_right.to_lowercase().as_str() {
+        "dfs" | "depth-first" => Ok(Traversal::DepthFirst),
+        "bfs" | "breadth-first" => Ok(Traversal::BreadthFirst),
+        _ => Err(Error::argument(
+            left,
+            format!(r#"Failed to convert "{}" to a valid traversal order (dfs/bfs)."#, lossy_right),
+        )),
     }
+}
 
-    if let Ok(curr_dir) = env::current_dir() {
-        vec![curr_dir]
+pub fn threads(left: &str, right: OsString) -> Result<usize, Error> {
+    let lossy_right: Cow<str> = right.to_string_lossy();
 
-    } else {
-        eprintln!("Couldn't find any directories and couldn't default to the current one either");
-        process::exit(1);
+    match lossy_right.parse::<usize>() {
+        Ok(threads) if threads > 0 => Ok(threads),
+        _ => Err(Error::argument(
+            left,
+            format!(r#"Failed to convert "{}" to a valid number of threads (1 or more)."#, lossy_right),
+        )),
     }
 }
 
+/// Keeps the paths that can be listed, reporting every other one as a serious error the way `ls`
+/// reports an operand it cannot access, and only defaults to the current directory when no path
+/// was given at all.
+pub fn default_to_curr_dir(paths: Vec<PathBuf>) -> Result<Vec<PathBuf>, Error> {
+    let curr_exe: &PathBuf = &env::current_exe().unwrap_or_else(|_| PathBuf::new());
+
+    let paths: Vec<PathBuf> = paths.into_iter().filter(|path_buf| path_buf != curr_exe).collect();
+
+    if paths.is_empty() {
+        return match env::current_dir() {
+            Ok(curr_dir) => Ok(vec![curr_dir]),
+            Err(error) => Err(Error::argument(
+                "PATH",
+                format!("Couldn't default to the current directory: {}", error),
+            )),
+        };
+    }
+
+    let mut directories: Vec<PathBuf> = Vec::new();
+
+    for path_buf in paths {
+        let source: io::Error = match fs::metadata(&path_buf) {
+            Ok(metadata) if metadata.is_dir() => {
+                directories.push(path_buf);
+                continue;
+            }
+            Ok(_) => io::Error::from_raw_os_error(libc::ENOTDIR),
+            Err(error) => error,
+        };
+
+        error::report(&Error::Read { path: path_buf, source, is_root: true });
+    }
+
+    directories.sort_unstable();
+    Ok(directories)
+}
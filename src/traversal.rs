@@ -0,0 +1,280 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::display::{self, JsonDocument, Listing};
+use crate::error::Error;
+use crate::types::{Config, OutputFormat, Traversal, Visited};
+
+// Directories are numbered in the order they have to be shown in
+type Job = (usize, PathBuf);
+type Finished = (usize, Listing);
+
+// How many directories each worker may read ahead of the one being printed, which bounds how
+// many finished listings wait in memory
+const READ_AHEAD_PER_THREAD: usize = 4;
+
+fn show<W: Write>(
+    out: &mut W,
+    config: &Config,
+    listing: Listing,
+    is_root: bool,
+    document: &mut JsonDocument,
+) -> Result<(), Error> {
+    if config.output_format == OutputFormat::Json {
+        document.add(&listing);
+        display::report_errors(out, &listing.path, listing.errors, is_root)?;
+        Ok(())
+    } else {
+        display::display_path(out, config, listing, is_root)
+    }
+}
+
+/// Shows `path` and returns the subdirectories a recursive listing continues with.
+fn visit<W: Write>(
+    out: &mut W,
+    config: &Config,
+    path: &Path,
+    is_root: bool,
+    document: &mut JsonDocument,
+) -> Result<Vec<PathBuf>, Error> {
+    let subdirectories: Vec<PathBuf> = if display::is_streamable(config) {
+        display::stream_path(out, config, path, is_root)?
+    } else {
+        let listing: Listing = Listing::read(config, path);
+        let subdirectories: Vec<PathBuf> = listing.subdirectories.clone();
+        show(out, config, listing, is_root, document)?;
+        subdirectories
+    };
+
+    // Keeps stdout in step with notices printed to stderr in between directories
+    out.flush()?;
+
+    Ok(subdirectories)
+}
+
+/// Subdirectories of a directory found at `depth` that are still worth visiting, one level down,
+/// and the symlinks skipped for leading back to a directory that was already listed.
+///
+/// The skipped links are left for the caller to report once their parent has been printed.
+fn next_level(
+    config: &Config,
+    subdirectories: Vec<PathBuf>,
+    depth: usize,
+    visited: &mut Visited,
+) -> (Vec<(PathBuf, usize)>, Vec<PathBuf>) {
+    if config.max_depth.is_some_and(|max_depth| depth + 1 >= max_depth) {
+        return (Vec::new(), Vec::new());
+    }
+
+    // Followed symlinks can lead back up the tree, or to a directory listed elsewhere
+    let (children, revisited): (Vec<PathBuf>, Vec<PathBuf>) = subdirectories
+        .into_iter()
+        .partition(|subdirectory| visited.insert(subdirectory));
+
+    // Reaching one again without a link, below overlapping operands say, isn't worth a notice
+    let revisited: Vec<PathBuf> = revisited.into_iter().filter(|subdirectory| Visited::is_link(subdirectory)).collect();
+
+    let children: Vec<(PathBuf, usize)> = children.into_iter().map(|subdirectory| (subdirectory, depth + 1)).collect();
+
+    (children, revisited)
+}
+
+/// Queues `items`, kept in display order, right before or after everything already queued.
+fn schedule<T>(traversal: &Traversal, queue: &mut VecDeque<T>, items: Vec<T>) {
+    match traversal {
+        Traversal::DepthFirst => {
+            for item in items.into_iter().rev() {
+                queue.push_front(item);
+            }
+        }
+        Traversal::BreadthFirst => queue.extend(items),
+    }
+}
+
+fn walk_sequential<W: Write>(
+    out: &mut W,
+    config: &Config,
+    paths: Vec<PathBuf>,
+    document: &mut JsonDocument,
+) -> Result<(), Error> {
+    let mut visited: Visited = Visited::default();
+    let mut queue: VecDeque<(PathBuf, usize)> = VecDeque::new();
+
+    for path_buf in paths {
+        queue.push_back((path_buf, 0));
+    }
+
+    while let Some((path_buf, depth)) = queue.pop_front() {
+        // Operands are always listed, but only count as visited once their listing starts
+        if depth == 0 {
+            visited.insert(&path_buf);
+        }
+
+        let subdirectories: Vec<PathBuf> = visit(out, config, &path_buf, depth == 0, document)?;
+        let (children, revisited): (Vec<(PathBuf, usize)>, Vec<PathBuf>) =
+            next_level(config, subdirectories, depth, &mut visited);
+
+        for subdirectory in revisited {
+            Visited::report(&subdirectory);
+        }
+
+        schedule(&config.traversal, &mut queue, children);
+    }
+    Ok(())
+}
+
+/// Hands directories out to the workers, remembering the order they have to be shown in.
+///
+/// Directories are queued with their path until they are sent, which only happens while fewer
+/// than `read_ahead` are being read or waiting to be shown.
+struct Jobs {
+    sender: Sender<Job>,
+    order: VecDeque<(usize, usize, Option<PathBuf>)>,
+    next_id: usize,
+    in_flight: usize,
+    read_ahead: usize,
+}
+
+impl Jobs {
+    fn submit(&mut self, traversal: &Traversal, directories: Vec<(PathBuf, usize)>) {
+        let mut submitted: Vec<(usize, usize, Option<PathBuf>)> = Vec::new();
+
+        for (path_buf, depth) in directories {
+            submitted.push((self.next_id, depth, Some(path_buf)));
+            self.next_id += 1;
+        }
+
+        schedule(traversal, &mut self.order, submitted);
+        self.dispatch();
+    }
+
+    /// Sends the earliest directories in display order that haven't been sent yet.
+    fn dispatch(&mut self) {
+        for (index, (id, _, path_buf)) in self.order.iter_mut().enumerate() {
+            // The next directory to show is always sent, or nothing would ever free the window
+            if self.in_flight >= self.read_ahead && index > 0 {
+                break;
+            }
+
+            if let Some(path_buf) = path_buf.take() {
+                // Only fails once every worker is gone, in which case waiting on it fails as well
+                let _ = self.sender.send((*id, path_buf));
+                self.in_flight += 1;
+            }
+        }
+    }
+}
+
+fn walk_parallel<W: Write>(
+    out: &mut W,
+    config: &Config,
+    paths: Vec<PathBuf>,
+    document: &mut JsonDocument,
+) -> Result<(), Error> {
+    thread::scope(|scope| {
+        let (job_sender, job_receiver): (Sender<Job>, Receiver<Job>) = mpsc::channel();
+        let (listing_sender, listing_receiver): (Sender<Finished>, Receiver<Finished>) = mpsc::channel();
+        let job_receiver: Arc<Mutex<Receiver<Job>>> = Arc::new(Mutex::new(job_receiver));
+
+        for _ in 0..config.threads {
+            let job_receiver: Arc<Mutex<Receiver<Job>>> = Arc::clone(&job_receiver);
+            let listing_sender: Sender<Finished> = listing_sender.clone();
+
+            scope.spawn(move || loop {
+                let job = match job_receiver.lock() {
+                    Ok(receiver) => receiver.recv(),
+                    Err(_) => break,
+                };
+                let (id, path_buf): (usize, PathBuf) = match job {
+                    Ok(job) => job,
+                    Err(_) => break,
+                };
+
+                if listing_sender.send((id, Listing::read(config, &path_buf))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(listing_sender);
+
+        let mut jobs: Jobs = Jobs {
+            sender: job_sender,
+            order: VecDeque::new(),
+            next_id: 0,
+            in_flight: 0,
+            read_ahead: config.threads * READ_AHEAD_PER_THREAD,
+        };
+        let mut visited: Visited = Visited::default();
+
+        let roots: Vec<(PathBuf, usize)> = paths.into_iter().map(|path_buf| (path_buf, 0)).collect();
+        jobs.submit(&Traversal::BreadthFirst, roots);
+
+        // Listings come back in whatever order the workers finish them, but are shown in the
+        // order a single thread would have produced
+        let mut finished: HashMap<usize, Listing> = HashMap::new();
+
+        while let Some((id, depth, _)) = jobs.order.pop_front() {
+            let listing: Listing = loop {
+                if let Some(listing) = finished.remove(&id) {
+                    break listing;
+                }
+                match listing_receiver.recv() {
+                    Ok((finished_id, listing)) => {
+                        finished.insert(finished_id, listing);
+                    }
+                    Err(_) => return Ok(()),
+                }
+            };
+            jobs.in_flight -= 1;
+
+            if depth == 0 {
+                visited.insert(&listing.path);
+            }
+
+            // Queued before printing so the workers keep busy in the meantime
+            let (children, revisited): (Vec<(PathBuf, usize)>, Vec<PathBuf>) =
+                next_level(config, listing.subdirectories.clone(), depth, &mut visited);
+            jobs.submit(&config.traversal, children);
+
+            show(out, config, listing, depth == 0, document)?;
+            out.flush()?;
+
+            // Only now that the directory they were found in is out
+            for subdirectory in revisited {
+                Visited::report(&subdirectory);
+            }
+        }
+        // Dropping `jobs` closes the channel, which lets the workers return
+        Ok(())
+    })
+}
+
+pub fn call_recursive<W: Write>(
+    out: &mut W,
+    config: &Config,
+    paths: Vec<PathBuf>,
+    document: &mut JsonDocument,
+) -> Result<(), Error> {
+    // Streaming is about printing straight away, workers reading ahead would defeat it
+    if config.threads > 1 && !display::is_streamable(config) {
+        walk_parallel(out, config, paths, document)
+    } else {
+        walk_sequential(out, config, paths, document)
+    }
+}
+
+pub fn call_non_recursive<W: Write>(
+    out: &mut W,
+    config: &Config,
+    paths: Vec<PathBuf>,
+    document: &mut JsonDocument,
+) -> Result<(), Error> {
+    for path_buf in paths {
+        visit(out, config, &path_buf, true, document)?;
+    }
+    Ok(())
+}
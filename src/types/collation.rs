@@ -0,0 +1,86 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::ffi::{OsStr, OsString};
+use std::os::unix::ffi::OsStrExt;
+
+use unicode_normalization::UnicodeNormalization;
+
+use crate::types::NameOrder;
+
+/// Unicode normalisation applied to names before comparing them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Normalization {
+    None,
+    /// Composed, so a precomposed `é` and an `e` followed by a combining accent compare equal.
+    Nfc,
+    /// Decomposed, which also sorts accented letters right after their base letter.
+    Nfd,
+}
+
+/// Everything that decides how two names compare.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Collation {
+    pub name_order: NameOrder,
+    pub ignore_case: bool,
+    pub normalization: Normalization,
+    /// Skips leading ASCII punctuation such as `.` and `_`, so `.bashrc` sorts among the `b`s.
+    pub ignore_leading_punctuation: bool,
+}
+
+impl Default for Collation {
+    fn default() -> Self {
+        Self {
+            name_order: NameOrder::Bytes,
+            ignore_case: false,
+            normalization: Normalization::None,
+            ignore_leading_punctuation: false,
+        }
+    }
+}
+
+impl Collation {
+    /// What `name` is compared as, borrowed as is when no option changes it.
+    ///
+    /// Sorts compute it once per name and compare with `compare_keys`, since it may allocate.
+    pub fn key<'a>(&self, name: &'a OsStr) -> Cow<'a, OsStr> {
+        if !self.ignore_case && self.normalization == Normalization::None && !self.ignore_leading_punctuation {
+            return Cow::Borrowed(name);
+        }
+
+        let lossy_name: Cow<str> = name.to_string_lossy();
+        let mut key: &str = &lossy_name;
+
+        if self.ignore_leading_punctuation {
+            let trimmed: &str = key.trim_start_matches(|chr: char| chr.is_ascii_punctuation());
+            // Names made of punctuation only, like `...`, have nothing else to go by
+            if !trimmed.is_empty() {
+                key = trimmed;
+            }
+        }
+
+        let key: String = if self.ignore_case { key.to_lowercase() } else { key.to_string() };
+
+        let key: String = match self.normalization {
+            Normalization::None => key,
+            Normalization::Nfc => key.nfc().collect(),
+            Normalization::Nfd => key.nfd().collect(),
+        };
+
+        Cow::Owned(OsString::from(key))
+    }
+
+    pub fn compare(&self, left: &OsStr, right: &OsStr) -> Ordering {
+        self.compare_keys(left, &self.key(left), right, &self.key(right))
+    }
+
+    /// Compares two names through the keys `key` made of them.
+    ///
+    /// Names the options or the name order make equal, like `README` and `readme` when ignoring
+    /// case or `file01` and `file1` in natural order, still fall back to their bytes so the result
+    /// never depends on the sorting algorithm.
+    pub fn compare_keys(&self, left: &OsStr, left_key: &OsStr, right: &OsStr, right_key: &OsStr) -> Ordering {
+        self.name_order
+            .compare(left_key, right_key)
+            .then_with(|| left.as_bytes().cmp(right.as_bytes()))
+    }
+}
@@ -0,0 +1,104 @@
+use std::env;
+use std::io::{self, IsTerminal};
+
+use crate::types::RgbColor;
+
+/// When to colour the output, following GNU `ls --color`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColourWhen {
+    Auto,
+    Always,
+    Never,
+}
+
+/// How many colours the output may use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColourDepth {
+    None,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+fn is_set(name: &str) -> bool {
+    env::var_os(name).is_some_and(|value| !value.is_empty() && value != "0")
+}
+
+impl ColourDepth {
+    fn from_terminal() -> Self {
+        if let Ok(colorterm) = env::var("COLORTERM") {
+            if colorterm == "truecolor" || colorterm == "24bit" {
+                return ColourDepth::TrueColor;
+            }
+        }
+
+        match env::var("TERM") {
+            Ok(term) if term == "dumb" => ColourDepth::None,
+            Ok(term) if term.contains("256color") => ColourDepth::Ansi256,
+            Ok(term) if term.contains("truecolor") || term.contains("direct") => ColourDepth::TrueColor,
+            _ => ColourDepth::Ansi16,
+        }
+    }
+
+    /// Resolves `when` against the environment and stdout.
+    ///
+    /// `NO_COLOR` and `CLICOLOR=0` disable automatic colouring, `CLICOLOR_FORCE` enables it even
+    /// when stdout isn't a terminal. `always` still downgrades colours on limited terminals.
+    pub fn detect(when: ColourWhen) -> Self {
+        match when {
+            ColourWhen::Never => return ColourDepth::None,
+            ColourWhen::Always => {}
+            ColourWhen::Auto => {
+                if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+                    return ColourDepth::None;
+                }
+                if !is_set("CLICOLOR_FORCE") {
+                    if env::var_os("CLICOLOR").is_some_and(|value| value == "0") {
+                        return ColourDepth::None;
+                    }
+                    if !io::stdout().is_terminal() {
+                        return ColourDepth::None;
+                    }
+                }
+            }
+        }
+
+        match Self::from_terminal() {
+            // Colours were explicitly requested, a dumb terminal still gets the basic ones
+            ColourDepth::None if when == ColourWhen::Always => ColourDepth::Ansi16,
+            depth => depth,
+        }
+    }
+
+    /// SGR parameters selecting `colour` as the foreground, approximated to fit the depth.
+    pub fn foreground(&self, colour: &RgbColor) -> Option<String> {
+        match self {
+            ColourDepth::None => None,
+            ColourDepth::Ansi16 => {
+                let index: u8 = colour.to_ansi16();
+                let code: u8 = if index < 8 { 30 + index } else { 90 + index - 8 };
+                Some(code.to_string())
+            }
+            ColourDepth::Ansi256 => Some(format!("38;5;{}", colour.to_ansi256())),
+            ColourDepth::TrueColor => Some(format!("38;2;{};{};{}", colour.red, colour.green, colour.blue)),
+        }
+    }
+
+    /// Wraps `text` in the escape sequences for the given colour and SGR digits.
+    ///
+    /// Digits follow `--files` and friends: `0` is a no-op and `6` is treated as `5` (blink).
+    pub fn paint(&self, text: &str, colour: &RgbColor, codes: &[u8]) -> String {
+        if *self == ColourDepth::None {
+            return text.to_string();
+        }
+
+        let mut parameters: Vec<String> = codes
+            .iter()
+            .filter(|&&code| (1..=9).contains(&code))
+            .map(|&code| if code == 6 { 5 } else { code }.to_string())
+            .collect();
+        parameters.extend(self.foreground(colour));
+
+        format!("\x1b[{}m{}\x1b[0m", parameters.join(";"), text)
+    }
+}
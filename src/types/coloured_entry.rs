@@ -1,26 +1,60 @@
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::ffi::{OsStr, OsString};
 use std::fs::{self, DirEntry, FileType, Metadata};
 use std::io::Error;
-use std::os::unix::fs::PermissionsExt;
-use std::path::PathBuf;
+use std::os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
 use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
-use colored::Colorize;
+use crate::types::{Collation, Config, GitStatus, RgbColor, Style};
 
-use crate::types::{Config, RgbColor};
+// Vivid enough to tell hues apart while staying inside sRGB for most of them
+const GENERATED_CHROMA: f64 = 0.14;
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Kind {
     Directory,
+    // Sticky wins over other-writable, so `/tmp` is a sticky directory
+    StickyDirectory,
+    OtherWritableDirectory,
     File,
     Executable,
+    Setuid,
+    Setgid,
     Symlink,
+    BrokenSymlink,
+    Fifo,
+    Socket,
+    BlockDevice,
+    CharDevice,
     Unknown,
 }
 
+impl Kind {
+    pub fn is_directory(&self) -> bool {
+        matches!(self, Kind::Directory | Kind::StickyDirectory | Kind::OtherWritableDirectory)
+    }
+
+    /// Character appended by `--classify`, the same ones `ls -F` uses.
+    pub fn indicator(&self) -> Option<&'static str> {
+        match self {
+            Kind::Directory | Kind::StickyDirectory | Kind::OtherWritableDirectory => Some("/"),
+            Kind::Executable | Kind::Setuid | Kind::Setgid => Some("*"),
+            Kind::Symlink | Kind::BrokenSymlink => Some("@"),
+            Kind::Fifo => Some("|"),
+            Kind::Socket => Some("="),
+            Kind::File | Kind::BlockDevice | Kind::CharDevice | Kind::Unknown => None,
+        }
+    }
+}
+
+// Same limit as the kernel
+const MAX_LINK_HOPS: usize = 40;
+
 pub struct ColouredEntry {
     // Front stuff
     pub name: OsString,
@@ -29,6 +63,11 @@ pub struct ColouredEntry {
     pub colour: RgbColor,
     pub path: PathBuf,
     len: usize,
+    // How the Ord impl compares names and extensions
+    pub collation: Collation,
+
+    // Raw targets of each link in the chain starting at this entry, empty for anything else
+    pub link_targets: Vec<PathBuf>,
 
     // Acquired from Metadata
     pub kind: Kind,
@@ -36,41 +75,80 @@ pub struct ColouredEntry {
     pub created_at: Option<SystemTime>,
     pub modified_at: Option<SystemTime>,
     pub accessed_at: Option<SystemTime>,
+    pub mode: Option<u32>,
+    pub links: Option<u64>,
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+
+    // Filled in per directory, only when asked for
+    pub git_status: Option<GitStatus>,
 }
 
 impl ColouredEntry {
-    fn make_colors(config: &Config, lossy_name: &str, extension: &Option<OsString>) -> RgbColor {
-        let mut prod: usize = config.color_seed;
+    /// Colour generated for a name, from its extension when it has one.
+    pub fn make_colors(config: &Config, lossy_name: &str, extension: &Option<OsString>) -> RgbColor {
+        let key: Cow<str> = match extension {
+            Some(ext) => ext.to_string_lossy(),
+            None => Cow::Borrowed(lossy_name),
+        };
 
-        if let Some(ext) = extension {
-            for byte in ext.to_string_lossy().bytes() {
-                prod = prod.wrapping_mul(byte as usize);
-            }
-        } else {
-            for byte in lossy_name.bytes() {
-                prod = prod.wrapping_mul(byte as usize);
-            }
+        // Seeded FNV-1a followed by a murmur3 finaliser, so that short keys still use every bit
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325 ^ config.color_seed as u64;
+        for byte in key.bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
         }
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+        hash ^= hash >> 33;
+
+        let hue: f64 = (hash & 0xffff) as f64 / 65536.0 * 360.0;
 
-        let (green, blue): (usize, usize) = (prod / 255, prod % 255);
-        let (mut red, green): (usize, usize) = (green / 255, green % 255);
-        red %= 255;
+        let (min_lightness, max_lightness): (f64, f64) = config.lightness_range;
+        let lightness: f64 =
+            min_lightness + ((hash >> 16) & 0xffff) as f64 / 65535.0 * (max_lightness - min_lightness);
 
-        RgbColor { red, green, blue }
+        RgbColor::from_oklch(lightness, GENERATED_CHROMA, hue)
     }
 
-    fn make_kind(mode: usize, file_type: FileType) -> Kind {
+    fn make_kind(mode: usize, file_type: FileType, path: &Path) -> Kind {
         if file_type.is_file() {
-            if mode & 0o1111 != 0 {
+            if mode & 0o4000 != 0 {
+                Kind::Setuid
+            } else if mode & 0o2000 != 0 {
+                Kind::Setgid
+            } else if mode & 0o111 != 0 {
                 // executable
                 Kind::Executable
             } else {
                 Kind::File
             }
         } else if file_type.is_dir() {
-            Kind::Directory
+            if mode & 0o1000 != 0 {
+                Kind::StickyDirectory
+            } else if mode & 0o002 != 0 {
+                Kind::OtherWritableDirectory
+            } else {
+                Kind::Directory
+            }
+        } else if file_type.is_symlink() {
+            if fs::metadata(path).is_err() {
+                Kind::BrokenSymlink
+            } else {
+                Kind::Symlink
+            }
+        } else if file_type.is_fifo() {
+            Kind::Fifo
+        } else if file_type.is_socket() {
+            Kind::Socket
+        } else if file_type.is_block_device() {
+            Kind::BlockDevice
+        } else if file_type.is_char_device() {
+            Kind::CharDevice
         } else {
-            Kind::Symlink
+            Kind::Unknown
         }
     }
 
@@ -79,8 +157,11 @@ impl ColouredEntry {
         file_name: &OsString,
         kind: &Kind,
         color: &RgbColor,
+        style: Option<&Style>,
+        icon: Option<&str>,
+        classify: bool,
     ) -> (OsString, usize) {
-        let (codes, maybe_prefix, maybe_suffix): (&Vec<u8>, Option<OsString>, Option<OsString>) =
+        let (kind_codes, maybe_prefix, maybe_suffix): (&Vec<u8>, Option<OsString>, Option<OsString>) =
             match kind {
                 Kind::File => (
                     &config.files,
@@ -92,16 +173,61 @@ impl ColouredEntry {
                     config.prefix.directories.clone(),
                     config.suffix.directories.clone(),
                 ),
+                Kind::StickyDirectory => (
+                    &config.sticky_directories,
+                    config.prefix.sticky_directories.clone(),
+                    config.suffix.sticky_directories.clone(),
+                ),
+                Kind::OtherWritableDirectory => (
+                    &config.other_writable_directories,
+                    config.prefix.other_writable_directories.clone(),
+                    config.suffix.other_writable_directories.clone(),
+                ),
                 Kind::Executable => (
                     &config.executables,
                     config.prefix.executables.clone(),
                     config.suffix.executables.clone(),
                 ),
+                Kind::Setuid => (
+                    &config.setuid,
+                    config.prefix.setuid.clone(),
+                    config.suffix.setuid.clone(),
+                ),
+                Kind::Setgid => (
+                    &config.setgid,
+                    config.prefix.setgid.clone(),
+                    config.suffix.setgid.clone(),
+                ),
                 Kind::Symlink => (
                     &config.symlinks,
                     config.prefix.symlinks.clone(),
                     config.suffix.symlinks.clone(),
                 ),
+                Kind::BrokenSymlink => (
+                    &config.broken_symlinks,
+                    config.prefix.broken_symlinks.clone(),
+                    config.suffix.broken_symlinks.clone(),
+                ),
+                Kind::Fifo => (
+                    &config.fifos,
+                    config.prefix.fifos.clone(),
+                    config.suffix.fifos.clone(),
+                ),
+                Kind::Socket => (
+                    &config.sockets,
+                    config.prefix.sockets.clone(),
+                    config.suffix.sockets.clone(),
+                ),
+                Kind::BlockDevice => (
+                    &config.block_devices,
+                    config.prefix.block_devices.clone(),
+                    config.suffix.block_devices.clone(),
+                ),
+                Kind::CharDevice => (
+                    &config.char_devices,
+                    config.prefix.char_devices.clone(),
+                    config.suffix.char_devices.clone(),
+                ),
                 Kind::Unknown => (
                     &config.unknowns,
                     config.prefix.unknowns.clone(),
@@ -109,6 +235,15 @@ impl ColouredEntry {
                 ),
             };
 
+        // An explicit suffix wins over the indicator
+        let maybe_suffix: Option<OsString> = if classify {
+            maybe_suffix.or_else(|| kind.indicator().map(OsString::from))
+        } else {
+            maybe_suffix
+        };
+
+        let codes: &Vec<u8> = style.and_then(|style| style.codes.as_ref()).unwrap_or(kind_codes);
+
         let mut len: usize = 0;
         let mut formatted_content: String = String::new();
 
@@ -128,47 +263,110 @@ impl ColouredEntry {
             formatted_content.push_str(&lossy_suffix);
         }
 
-        let mut styled_content =
-            formatted_content.truecolor(color.red as u8, color.green as u8, color.blue as u8);
-
-        for code in codes {
-            styled_content = match code {
-                0 => styled_content,
-                1 => styled_content.bold(),
-                2 => styled_content.dimmed(),
-                3 => styled_content.italic(),
-                4 => styled_content.underline(),
-                5 => styled_content.blink(),
-                6 => styled_content.blink(),
-                7 => styled_content.reversed(),
-                8 => styled_content.hidden(),
-                9 => styled_content.strikethrough(),
-                _ => styled_content,
+        let mut styled_content: String = config.colour_depth.paint(&formatted_content, color, codes);
+
+        // Icons only take the colour, bold or underlined glyphs tend to render badly
+        if let Some(icon) = icon {
+            len += icon.width() + 1;
+            styled_content = format!("{} {}", config.colour_depth.paint(icon, color, &[]), styled_content);
+        }
+
+        (OsString::from(styled_content), len)
+    }
+
+    /// Follows the link at `path` one hop at a time, pairing each raw target with where it leads.
+    fn read_link_chain(path: &Path) -> Vec<(PathBuf, PathBuf)> {
+        let mut chain: Vec<(PathBuf, PathBuf)> = Vec::new();
+        let mut current: PathBuf = path.to_path_buf();
+
+        while chain.len() < MAX_LINK_HOPS {
+            let target: PathBuf = match fs::read_link(&current) {
+                Ok(target) => target,
+                Err(_) => break,
+            };
+
+            // Relative targets are relative to the directory holding the link
+            current = match current.parent() {
+                Some(parent) => parent.join(&target),
+                None => target.clone(),
             };
+            chain.push((target, current.clone()));
+
+            // Stop once the chain comes back on itself, showing where it does
+            if chain[..chain.len() - 1].iter().any(|(_, resolved)| *resolved == current) || current == path {
+                break;
+            }
+        }
+
+        chain
+    }
+
+    /// Appends ` -> target` for every hop of the link, each styled like what it points to.
+    fn add_link_targets(&mut self, config: &Config, chain: Vec<(PathBuf, PathBuf)>) {
+        let mut formatted_name: OsString = self.formatted_name.clone();
+
+        for (target, resolved) in chain {
+            let hop: ColouredEntry = Self::from_path(target.into_os_string(), resolved, config, true);
+
+            formatted_name.push(" -> ");
+            formatted_name.push(&hop.formatted_name);
+            self.len += 4 + hop.len;
         }
 
-        (OsString::from(styled_content.to_string()), len)
+        self.formatted_name = formatted_name;
     }
 
+    /// Displayed width of `formatted_name`, escape codes left out.
     pub fn len(&self) -> usize {
         self.len
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Puts an already styled marker in front of the name, `width` being its displayed width.
+    pub fn add_marker(&mut self, marker: &str, width: usize) {
+        let mut formatted_name: OsString = OsString::from(marker);
+        formatted_name.push(&self.formatted_name);
+
+        self.formatted_name = formatted_name;
+        self.len += width;
+    }
+
     pub fn new(file_name: OsString, dir_entry: &DirEntry, config: &Config) -> Self {
-        let extension: Option<OsString> = dir_entry.path().extension().map(OsStr::to_os_string);
+        let mut entry: Self = Self::from_path(file_name, dir_entry.path(), config, false);
+
+        if dir_entry.file_type().is_ok_and(|file_type| file_type.is_symlink()) {
+            let chain: Vec<(PathBuf, PathBuf)> = Self::read_link_chain(&entry.path);
+            entry.link_targets = chain.iter().map(|(target, _)| target.clone()).collect();
+
+            if config.link_targets {
+                entry.add_link_targets(config, chain);
+            }
+        }
 
-        let lossy_name: &str = &file_name.to_string_lossy();
-        let mut colour: RgbColor = Self::make_colors(config, lossy_name, &extension);
-        colour.pad_lowest(config.minimal_rgb_sum);
+        entry
+    }
 
-        let path_buf: PathBuf = dir_entry.path();
+    /// Builds the entry found at `path_buf`, shown as `file_name`.
+    ///
+    /// Link targets are never followed and get no icon; a target that doesn't exist takes the
+    /// broken symlink style.
+    fn from_path(file_name: OsString, path_buf: PathBuf, config: &Config, is_link_target: bool) -> Self {
+        let extension: Option<OsString> = path_buf.extension().map(OsStr::to_os_string);
+
+        // Link targets are shown as written, but styled after the file they point to
+        let lookup_name: OsString = path_buf.file_name().map_or_else(|| file_name.clone(), OsStr::to_os_string);
+        let lossy_name: &str = &lookup_name.to_string_lossy();
 
         // Stuff extracted from metadata
 
-        let maybe_metadata: Result<Metadata, Error> = if config.follow_symlinks {
-            fs::metadata(&path_buf)
+        let maybe_metadata: Result<Metadata, Error> = if config.follow_symlinks && !is_link_target {
+            // Broken links can't be followed but can still be described
+            fs::metadata(&path_buf).or_else(|_| fs::symlink_metadata(&path_buf))
         } else {
-            dir_entry.metadata()
+            fs::symlink_metadata(&path_buf)
         };
 
         let mut kind: Kind = Kind::Unknown;
@@ -178,6 +376,16 @@ impl ColouredEntry {
         let mut modified_at: Option<SystemTime> = None;
         let mut accessed_at: Option<SystemTime> = None;
 
+        let mut mode: Option<u32> = None;
+        let mut links: Option<u64> = None;
+        let mut uid: Option<u32> = None;
+        let mut gid: Option<u32> = None;
+
+        let mut ls_colors_style: Option<&Style> = None;
+
+        // Missing link targets are styled as broken links, but aren't links themselves
+        let classify: bool = config.classify && maybe_metadata.is_ok();
+
         if let Ok(metadata) = maybe_metadata {
             let file_type: FileType = metadata.file_type();
 
@@ -187,30 +395,69 @@ impl ColouredEntry {
             modified_at = metadata.modified().ok();
             accessed_at = metadata.accessed().ok();
 
+            mode = Some(metadata.mode());
+            links = Some(metadata.nlink());
+            uid = Some(metadata.uid());
+            gid = Some(metadata.gid());
+
+            if let Some(ls_colors) = &config.ls_colors {
+                ls_colors_style = ls_colors.style_for(lossy_name, &file_type, metadata.mode(), &path_buf);
+            }
+
             if cfg!(unix) {
                 let retrieved_mode: usize = metadata.permissions().mode() as usize;
-                kind = Self::make_kind(retrieved_mode, file_type);
+                kind = Self::make_kind(retrieved_mode, file_type, &path_buf);
             } else {
-                kind = Self::make_kind(0, file_type);
+                kind = Self::make_kind(0, file_type, &path_buf);
             }
+        } else if is_link_target {
+            kind = Kind::BrokenSymlink;
         }
 
+        // A theme takes precedence over LS_COLORS, which takes precedence over generated colours
+        let lossy_extension: Option<Cow<str>> = extension.as_ref().map(|ext| ext.to_string_lossy());
+        let style: Option<&Style> = config
+            .theme
+            .as_ref()
+            .and_then(|theme| theme.style_for(lossy_name, lossy_extension.as_deref(), &kind))
+            .or(ls_colors_style);
+
+        let colour: RgbColor = match style.and_then(|style| style.colour.clone()) {
+            Some(colour) => colour,
+            None => Self::make_colors(config, lossy_name, &extension),
+        };
+
+        let icon: Option<&str> = if is_link_target {
+            None
+        } else {
+            config.icons.icon_for(lossy_name, lossy_extension.as_deref(), &kind)
+        };
+
         let (formatted_name, len): (OsString, usize) =
-            Self::make_formatted_name(config, &file_name, &kind, &colour);
+            Self::make_formatted_name(config, &file_name, &kind, &colour, style, icon, classify);
 
         Self {
             name: file_name,
             formatted_name,
             extension,
             colour,
-            path: dir_entry.path(),
+            path: path_buf,
             len,
+            collation: config.collation,
+
+            link_targets: Vec::new(),
 
             kind,
             size_bytes,
             created_at,
             modified_at,
             accessed_at,
+            mode,
+            links,
+            uid,
+            gid,
+
+            git_status: None,
         }
     }
 }
@@ -219,22 +466,26 @@ impl Eq for ColouredEntry {}
 
 impl PartialEq for ColouredEntry {
     fn eq(&self, other: &Self) -> bool {
-        &self.path == &other.path
+        self.path == other.path
     }
 }
 
 impl Ord for ColouredEntry {
     fn cmp(&self, other: &Self) -> Ordering {
-        let other_cmp: (&Kind, &Option<OsString>, &OsString) =
-            (&other.kind, &other.extension, &other.name);
+        let extension_cmp: Ordering = match (&self.extension, &other.extension) {
+            (Some(extension), Some(other_extension)) => self.collation.compare(extension, other_extension),
+            (extension, other_extension) => extension.cmp(other_extension),
+        };
 
-        (&self.kind, &self.extension, &self.name).cmp(&other_cmp)
+        self.kind
+            .cmp(&other.kind)
+            .then(extension_cmp)
+            .then_with(|| self.collation.compare(&self.name, &other.name))
     }
 }
 
 impl PartialOrd for ColouredEntry {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        let cmp: Ordering = self.cmp(other);
-        Some(cmp)
+        Some(self.cmp(other))
     }
 }
@@ -1,33 +1,82 @@
+use std::env;
 use std::ffi::OsString;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::thread;
 use std::time::SystemTime;
 
-use term_size;
-use regex::Regex;
+use crate::types::{Collation, ColourDepth, ColourWhen, IconSet, LsColors, Pattern, Theme};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SortingReference {
-    Default,
+    /// Directories before everything else, what `--group-directories-first` stands for.
+    Directories,
+    Kind,
     Name,
+    Natural,
+    Version,
     Size,
     Extension,
     CreationDate,
     AccessDate,
     ModificationDate,
     Colour,
+    /// Around the colour wheel, then from dark to light.
+    Hue,
+}
+
+/// One key of a sort specification, such as `-size` for the largest entries first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub field: SortingReference,
+    pub descending: bool,
+}
+
+impl SortKey {
+    pub fn ascending(field: SortingReference) -> Self {
+        Self { field, descending: false }
+    }
 }
 
 #[derive(Debug)]
 pub enum SizeMeasurementUnit {
     Bytes,
     Bits,
-} 
+    SiBytes,
+    SiBits,
+    IecBytes,
+    IecBits,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Ndjson,
+}
+
+/// Order in which recursive listings visit subdirectories.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Traversal {
+    DepthFirst,
+    BreadthFirst,
+}
 
 #[derive(Debug, Default)]
 pub struct AddedStr {
     pub files: Option<OsString>,
     pub directories: Option<OsString>,
+    pub sticky_directories: Option<OsString>,
+    pub other_writable_directories: Option<OsString>,
     pub executables: Option<OsString>,
+    pub setuid: Option<OsString>,
+    pub setgid: Option<OsString>,
     pub symlinks: Option<OsString>,
+    pub broken_symlinks: Option<OsString>,
+    pub fifos: Option<OsString>,
+    pub sockets: Option<OsString>,
+    pub block_devices: Option<OsString>,
+    pub char_devices: Option<OsString>,
     pub unknowns: Option<OsString>,
 }
 
@@ -39,24 +88,47 @@ pub struct Config {
     pub titles: Vec<u8>,
     pub files: Vec<u8>,
     pub directories: Vec<u8>,
+    pub sticky_directories: Vec<u8>,
+    pub other_writable_directories: Vec<u8>,
     pub executables: Vec<u8>,
+    pub setuid: Vec<u8>,
+    pub setgid: Vec<u8>,
     pub symlinks: Vec<u8>,
+    pub broken_symlinks: Vec<u8>,
+    pub fifos: Vec<u8>,
+    pub sockets: Vec<u8>,
+    pub block_devices: Vec<u8>,
+    pub char_devices: Vec<u8>,
     pub unknowns: Vec<u8>,
 
     pub prefix: AddedStr,
     pub suffix: AddedStr,
+    pub classify: bool,
 
+    pub colour_depth: ColourDepth,
     pub color_seed: usize,
-    pub minimal_rgb_sum: usize,
+    pub lightness_range: (f64, f64),
+    pub ls_colors: Option<LsColors>,
+    pub theme: Option<Theme>,
+    pub icons: IconSet,
 
     pub one_per_line: bool,
     pub is_long_listing: bool,
+    pub tree: bool,
+    pub ascii_guides: bool,
+    pub output_format: OutputFormat,
+    pub git_status: bool,
 
     pub time_formatting: OsString,
     pub unit_size: SizeMeasurementUnit,
+    pub size_precision: usize,
+    pub thousands_separator: Option<OsString>,
 
     // Sorting
-    pub sort_by: SortingReference,
+    // Keys in order of precedence, empty to keep the order the file system returns entries in
+    pub sort_by: Vec<SortKey>,
+    pub collation: Collation,
+    // Shorthands for a leading `directories` key and for flipping every other key
     pub group_directories_first: bool,
     pub reverse: bool,
 
@@ -67,16 +139,39 @@ pub struct Config {
     // Ignored stuff
     pub show_dotfiles: bool,
     pub show_backups: bool,
+    pub only_broken_symlinks: bool,
+    pub link_targets: bool,
 
     // Searching options
     pub recursive: bool,
     pub follow_symlinks: bool,
+    pub max_depth: Option<usize>,
+    pub traversal: Traversal,
+    pub threads: usize,
 
-    pub include_pattern: Option<Regex>,
-    pub exclude_pattern: Option<Regex>,
+    // Entries are shown when they match any include pattern, if there is one, and no exclude
+    // pattern. Exclusions also prune recursion, inclusions only hide entries.
+    pub include_patterns: Vec<Pattern>,
+    pub exclude_patterns: Vec<Pattern>,
+    pub ignore_pattern_case: bool,
 
     // Auto generated //
     pub term_width: Option<usize>,
+    // What titles and path patterns are relative to, looked up once rather than for every entry
+    pub current_dir: Option<PathBuf>,
+}
+
+// Same lookup order as setlocale(3) for LC_CTYPE
+fn locale_is_utf8() -> bool {
+    for name in ["LC_ALL", "LC_CTYPE", "LANG"].iter() {
+        if let Ok(locale) = env::var(name) {
+            if !locale.is_empty() {
+                let locale: String = locale.to_lowercase();
+                return locale.contains("utf-8") || locale.contains("utf8");
+            }
+        }
+    }
+    false
 }
 
 impl Default for Config {
@@ -96,22 +191,52 @@ impl Default for Config {
             titles: Vec::new(),
             files: Vec::new(),
             directories: Vec::new(),
+            sticky_directories: vec![4],
+            other_writable_directories: vec![7],
             executables: vec![1],
+            setuid: vec![1, 7],
+            setgid: vec![1, 7],
             symlinks: vec![4],
+            broken_symlinks: vec![9],
+            fifos: vec![3],
+            sockets: vec![1, 3],
+            block_devices: vec![1, 4],
+            char_devices: vec![1, 4],
             unknowns: vec![3],
 
             prefix: AddedStr::default(),
-            suffix: AddedStr {directories: Some(OsString::from("/")), ..Default::default()},
+            suffix: AddedStr {
+                directories: Some(OsString::from("/")),
+                sticky_directories: Some(OsString::from("/")),
+                other_writable_directories: Some(OsString::from("/")),
+                ..Default::default()
+            },
+            classify: false,
 
+            colour_depth: ColourDepth::detect(ColourWhen::Auto),
             color_seed,
-            minimal_rgb_sum: 512,
+            lightness_range: (0.72, 0.86),
+            ls_colors: None,
+            theme: None,
+            icons: IconSet::None,
             one_per_line: false,
             is_long_listing: false,
+            tree: false,
+            ascii_guides: !locale_is_utf8(),
+            output_format: OutputFormat::Text,
+            git_status: false,
 
-            time_formatting: OsString::from("%b %m %H:%M"),
+            time_formatting: OsString::from("%b %d %H:%M"),
             unit_size: SizeMeasurementUnit::Bytes,
+            size_precision: 1,
+            thousands_separator: None,
 
-            sort_by: SortingReference::Default,
+            sort_by: vec![
+                SortKey::ascending(SortingReference::Kind),
+                SortKey::ascending(SortingReference::Extension),
+                SortKey::ascending(SortingReference::Name),
+            ],
+            collation: Collation::default(),
             group_directories_first: false,
             reverse: false,
 
@@ -120,14 +245,43 @@ impl Default for Config {
 
             show_dotfiles: false,
             show_backups: false,
+            only_broken_symlinks: false,
+            link_targets: false,
 
             recursive: false,
             follow_symlinks: false,
+            max_depth: None,
+            traversal: Traversal::DepthFirst,
+            threads: thread::available_parallelism().map_or(1, NonZeroUsize::get),
 
-            include_pattern: None,
-            exclude_pattern: None,
+            include_patterns: Vec::new(),
+            exclude_patterns: Vec::new(),
+            ignore_pattern_case: false,
 
             term_width: term_size::dimensions().map(|(w, _)| w),
+            current_dir: env::current_dir().ok(),
+        }
+    }
+}
+
+impl Config {
+    /// Every key entries are sorted on, shorthands included: directories first when grouping
+    /// them, then `sort_by` with each direction flipped by `reverse`.
+    pub fn sort_keys(&self) -> Vec<SortKey> {
+        let mut sort_keys: Vec<SortKey> = Vec::new();
+
+        // Like `ls`, directories stay first when the rest of the order is reversed
+        if self.group_directories_first {
+            sort_keys.push(SortKey::ascending(SortingReference::Directories));
+        }
+
+        for sort_key in &self.sort_by {
+            sort_keys.push(SortKey {
+                field: sort_key.field,
+                descending: sort_key.descending != self.reverse,
+            });
         }
+
+        sort_keys
     }
-}
\ No newline at end of file
+}
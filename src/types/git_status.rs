@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Component, Path, PathBuf};
+
+use git2::{Repository, Status, StatusOptions};
+
+use crate::types::RgbColor;
+
+/// What git thinks of an entry, aggregated over every child for directories.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GitStatus {
+    pub conflicted: bool,
+    pub staged: bool,
+    pub modified: bool,
+    pub untracked: bool,
+    pub ignored: bool,
+}
+
+impl GitStatus {
+    fn from_status(status: Status) -> Self {
+        GitStatus {
+            conflicted: status.is_conflicted(),
+            staged: status.intersects(
+                Status::INDEX_NEW
+                    | Status::INDEX_MODIFIED
+                    | Status::INDEX_DELETED
+                    | Status::INDEX_RENAMED
+                    | Status::INDEX_TYPECHANGE,
+            ),
+            modified: status.intersects(
+                Status::WT_MODIFIED | Status::WT_DELETED | Status::WT_RENAMED | Status::WT_TYPECHANGE,
+            ),
+            untracked: status.is_wt_new(),
+            ignored: status.is_ignored(),
+        }
+    }
+
+    fn merge(&mut self, other: GitStatus, include_ignored: bool) {
+        self.conflicted |= other.conflicted;
+        self.staged |= other.staged;
+        self.modified |= other.modified;
+        self.untracked |= other.untracked;
+        self.ignored |= other.ignored && include_ignored;
+    }
+
+    /// Single character summary used by the grid layouts, the most pressing status wins.
+    pub fn marker(&self) -> char {
+        if self.conflicted {
+            'U'
+        } else if self.modified {
+            'M'
+        } else if self.staged {
+            'S'
+        } else if self.untracked {
+            '?'
+        } else if self.ignored {
+            '!'
+        } else {
+            ' '
+        }
+    }
+
+    /// Two character column for the long listing: the index state, then the work tree state.
+    pub fn column(&self) -> String {
+        if self.conflicted {
+            return String::from("UU");
+        }
+
+        let index: char = if self.staged { 'S' } else { '-' };
+        let work_tree: char = if self.modified {
+            'M'
+        } else if self.untracked {
+            '?'
+        } else if self.ignored {
+            '!'
+        } else {
+            '-'
+        };
+
+        format!("{}{}", index, work_tree)
+    }
+
+    // Picked from the 16 standard colours so that they survive any colour depth
+    pub fn colour(&self) -> RgbColor {
+        let index: u8 = if self.conflicted {
+            9
+        } else if self.modified {
+            3
+        } else if self.staged {
+            2
+        } else if self.untracked {
+            5
+        } else {
+            8
+        };
+
+        RgbColor::from_ansi16(index)
+    }
+
+    fn has_tracked_files(repository: &Repository, relative: &Path) -> bool {
+        let mut prefix: OsString = relative.as_os_str().to_os_string();
+        prefix.push("/");
+
+        repository.index().is_ok_and(|index| index.find_prefix(prefix).is_ok())
+    }
+
+    // Git reports an untracked or ignored directory as a whole and never what is inside of it,
+    // so every child takes after it
+    fn for_untracked_directory(
+        repository: &Repository,
+        directory: &Path,
+        relative: &Path,
+    ) -> Option<HashMap<OsString, GitStatus>> {
+        let mut statuses: HashMap<OsString, GitStatus> = HashMap::new();
+
+        for dir_entry in fs::read_dir(directory).ok()?.flatten() {
+            let file_name: OsString = dir_entry.file_name();
+            let ignored: bool = repository.is_path_ignored(relative.join(&file_name)).unwrap_or(false);
+
+            let status: GitStatus = GitStatus {
+                untracked: !ignored,
+                ignored,
+                ..GitStatus::default()
+            };
+            statuses.insert(file_name, status);
+        }
+
+        Some(statuses)
+    }
+
+    /// Statuses of the direct children of `directory`, keyed by file name.
+    ///
+    /// Only the local repository is read. Returns `None` outside of a work tree; children git has
+    /// nothing to say about are left out and should be considered clean.
+    pub fn for_directory(directory: &Path) -> Option<HashMap<OsString, GitStatus>> {
+        let repository: Repository = Repository::discover(directory).ok()?;
+
+        let workdir: PathBuf = fs::canonicalize(repository.workdir()?).ok()?;
+        let canonical_directory: PathBuf = fs::canonicalize(directory).ok()?;
+        let relative: &Path = canonical_directory.strip_prefix(&workdir).ok()?;
+
+        if !relative.as_os_str().is_empty() && !Self::has_tracked_files(&repository, relative) {
+            return Self::for_untracked_directory(&repository, directory, relative);
+        }
+
+        let mut options: StatusOptions = StatusOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(false)
+            .include_ignored(true)
+            .recurse_ignored_dirs(false);
+
+        if !relative.as_os_str().is_empty() {
+            options.pathspec(relative).disable_pathspec_match(true);
+        }
+
+        let mut statuses: HashMap<OsString, GitStatus> = HashMap::new();
+
+        for status_entry in repository.statuses(Some(&mut options)).ok()?.iter() {
+            let path: &Path = Path::new(OsStr::from_bytes(status_entry.path_bytes()));
+
+            let mut components = match path.strip_prefix(relative) {
+                Ok(inside) => inside.components(),
+                Err(_) => continue,
+            };
+
+            let child: OsString = match components.next() {
+                Some(Component::Normal(child)) => child.to_os_string(),
+                _ => continue,
+            };
+
+            // An ignored file deep inside a directory doesn't make the directory itself ignored
+            let is_direct_child: bool = components.next().is_none();
+
+            statuses
+                .entry(child)
+                .or_default()
+                .merge(Self::from_status(status_entry.status()), is_direct_child);
+        }
+
+        Some(statuses)
+    }
+}
@@ -0,0 +1,128 @@
+use crate::types::Kind;
+
+/// Glyph set used to decorate entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconSet {
+    None,
+    Nerd,
+    Emoji,
+}
+
+// (name, nerd font glyph, emoji)
+type Icon = (&'static str, &'static str, &'static str);
+
+const FILENAME_ICONS: [Icon; 14] = [
+    ("cargo.toml", "\u{e615}", "⚙️"),
+    ("cargo.lock", "\u{f023}", "🔒"),
+    ("makefile", "\u{e673}", "🛠️"),
+    ("dockerfile", "\u{f308}", "🐳"),
+    ("license", "\u{f0219}", "📜"),
+    ("readme", "\u{f00ba}", "📖"),
+    ("readme.md", "\u{f00ba}", "📖"),
+    (".gitignore", "\u{e702}", "🙈"),
+    (".gitattributes", "\u{e702}", "🐙"),
+    (".git", "\u{e5fb}", "🐙"),
+    ("package.json", "\u{e71e}", "📦"),
+    ("node_modules", "\u{e5fa}", "📦"),
+    ("go.mod", "\u{e627}", "🐹"),
+    ("requirements.txt", "\u{e606}", "🐍"),
+];
+
+const EXTENSION_ICONS: [Icon; 42] = [
+    ("rs", "\u{e7a8}", "🦀"),
+    ("toml", "\u{e615}", "⚙️"),
+    ("lock", "\u{f023}", "🔒"),
+    ("py", "\u{e606}", "🐍"),
+    ("js", "\u{e74e}", "📜"),
+    ("ts", "\u{e628}", "📜"),
+    ("go", "\u{e627}", "🐹"),
+    // Lone regional indicators are drawn two cells wide but measured as one, letters line up
+    ("c", "\u{e61e}", "C"),
+    ("h", "\u{e61e}", "H"),
+    ("cpp", "\u{e61d}", "➕"),
+    ("hpp", "\u{e61d}", "➕"),
+    ("java", "\u{e738}", "☕"),
+    ("rb", "\u{e739}", "💎"),
+    ("lua", "\u{e620}", "🌙"),
+    ("sh", "\u{f489}", "🐚"),
+    ("bash", "\u{f489}", "🐚"),
+    ("zsh", "\u{f489}", "🐚"),
+    ("html", "\u{e736}", "🌐"),
+    ("css", "\u{e749}", "🎨"),
+    ("json", "\u{e60b}", "🗂️"),
+    ("yaml", "\u{e6a8}", "🗂️"),
+    ("yml", "\u{e6a8}", "🗂️"),
+    ("xml", "\u{f05c0}", "🗂️"),
+    ("md", "\u{e73e}", "📝"),
+    ("txt", "\u{f0219}", "📄"),
+    ("pdf", "\u{f1c1}", "📕"),
+    ("png", "\u{f1c5}", "🖼️"),
+    ("jpg", "\u{f1c5}", "🖼️"),
+    ("jpeg", "\u{f1c5}", "🖼️"),
+    ("gif", "\u{f1c5}", "🖼️"),
+    ("svg", "\u{f1c5}", "🖼️"),
+    ("mp3", "\u{f1c7}", "🎵"),
+    ("flac", "\u{f1c7}", "🎵"),
+    ("mp4", "\u{f1c8}", "🎬"),
+    ("mkv", "\u{f1c8}", "🎬"),
+    ("zip", "\u{f410}", "🗜️"),
+    ("tar", "\u{f410}", "🗜️"),
+    ("gz", "\u{f410}", "🗜️"),
+    ("xz", "\u{f410}", "🗜️"),
+    ("7z", "\u{f410}", "🗜️"),
+    ("o", "\u{f471}", "🧱"),
+    ("so", "\u{f471}", "🧱"),
+];
+
+fn kind_icon(kind: &Kind) -> Icon {
+    match kind {
+        Kind::Directory => ("", "\u{f115}", "📁"),
+        Kind::StickyDirectory => ("", "\u{f0c09}", "📌"),
+        Kind::OtherWritableDirectory => ("", "\u{f0256}", "📂"),
+        Kind::Executable => ("", "\u{f489}", "🚀"),
+        Kind::Setuid | Kind::Setgid => ("", "\u{f084}", "🔑"),
+        Kind::Symlink => ("", "\u{f481}", "🔗"),
+        Kind::BrokenSymlink => ("", "\u{f127}", "💔"),
+        Kind::Fifo => ("", "\u{f07e4}", "🚰"),
+        Kind::Socket => ("", "\u{f0eb0}", "🔌"),
+        Kind::BlockDevice => ("", "\u{f02ca}", "💽"),
+        Kind::CharDevice => ("", "\u{f0375}", "⌨️"),
+        Kind::File => ("", "\u{f15b}", "📄"),
+        Kind::Unknown => ("", "\u{f128}", "❓"),
+    }
+}
+
+impl IconSet {
+    /// Picks a glyph from the exact filename, then the extension, then the kind of the entry.
+    ///
+    /// Extensions are only considered for regular files so that `foo.d/` still looks like a
+    /// directory.
+    pub fn icon_for(&self, lossy_name: &str, extension: Option<&str>, kind: &Kind) -> Option<&'static str> {
+        if *self == IconSet::None {
+            return None;
+        }
+
+        let lowercase_name: String = lossy_name.to_lowercase();
+
+        let by_extension = || -> Option<Icon> {
+            if !matches!(kind, Kind::File | Kind::Executable | Kind::Setuid | Kind::Setgid) {
+                return None;
+            }
+            let extension: String = extension?.to_lowercase();
+            EXTENSION_ICONS.iter().find(|(name, _, _)| *name == extension).copied()
+        };
+
+        let icon: Icon = FILENAME_ICONS
+            .iter()
+            .find(|(name, _, _)| *name == lowercase_name)
+            .copied()
+            .or_else(by_extension)
+            .unwrap_or_else(|| kind_icon(kind));
+
+        match self {
+            IconSet::None => None,
+            IconSet::Nerd => Some(icon.1),
+            IconSet::Emoji => Some(icon.2),
+        }
+    }
+}
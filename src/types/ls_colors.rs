@@ -0,0 +1,209 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs::{self, FileType};
+use std::os::unix::fs::FileTypeExt;
+use std::path::Path;
+
+use crate::types::{RgbColor, Style};
+
+/// Styles imported from GNU `LS_COLORS` or from a `dircolors` database.
+///
+/// Indicators (`di`, `ln`, `ex`, ...) style whole kinds of entries while `*suffix` keys style
+/// regular files by name. Anything left uncovered keeps its generated colour, which is why the
+/// `fi` indicator only lends its attributes to files no suffix matches.
+#[derive(Debug, Default)]
+pub struct LsColors {
+    indicators: HashMap<String, Style>,
+    // Lowercased and sorted from longest to shortest so the most specific suffix wins
+    suffixes: Vec<(String, Style)>,
+}
+
+fn dircolors_indicator(keyword: &str) -> Option<&'static str> {
+    let indicator: &str = match keyword.to_uppercase().as_str() {
+        "NORMAL" | "NORM" => "no",
+        "FILE" => "fi",
+        "RESET" => "rs",
+        "DIR" => "di",
+        "LINK" | "LNK" | "SYMLINK" => "ln",
+        "MULTIHARDLINK" => "mh",
+        "FIFO" | "PIPE" => "pi",
+        "SOCK" => "so",
+        "DOOR" => "do",
+        "BLK" | "BLOCK" => "bd",
+        "CHR" | "CHAR" => "cd",
+        "ORPHAN" => "or",
+        "MISSING" => "mi",
+        "SETUID" => "su",
+        "SETGID" => "sg",
+        "CAPABILITY" => "ca",
+        "STICKY_OTHER_WRITABLE" => "tw",
+        "OTHER_WRITABLE" => "ow",
+        "STICKY" => "st",
+        "EXEC" => "ex",
+        _ => return None,
+    };
+    Some(indicator)
+}
+
+fn parse_extended_colour<'a>(codes: &mut impl Iterator<Item = &'a str>) -> Option<RgbColor> {
+    let mut next = || codes.next().and_then(|code| code.parse::<u8>().ok());
+
+    match next()? {
+        5 => Some(RgbColor::from_ansi256(next()?)),
+        2 => {
+            let (red, green, blue) = (next()?, next()?, next()?);
+            Some(RgbColor { red: red as usize, green: green as usize, blue: blue as usize })
+        }
+        _ => None,
+    }
+}
+
+impl LsColors {
+    /// Turns an SGR sequence such as `01;38;5;208` into a style.
+    ///
+    /// Attributes map onto the same digits as `--files` and friends, foreground colours become
+    /// an `RgbColor`, and backgrounds are skipped since entries are only ever coloured in front.
+    pub fn parse_sgr(sequence: &str) -> Style {
+        let mut colour: Option<RgbColor> = None;
+        let mut attributes: Vec<u8> = Vec::new();
+
+        let mut codes = sequence.split(';');
+
+        while let Some(code) = codes.next() {
+            let code: u8 = match code.parse::<u8>() {
+                Ok(code) => code,
+                Err(_) => continue,
+            };
+
+            match code {
+                0 => attributes.clear(),
+                1..=9 => attributes.push(code),
+                30..=37 => colour = Some(RgbColor::from_ansi16(code - 30)),
+                90..=97 => colour = Some(RgbColor::from_ansi16(code - 90 + 8)),
+                38 => colour = parse_extended_colour(&mut codes).or(colour),
+                48 => {
+                    parse_extended_colour(&mut codes);
+                }
+                _ => {}
+            }
+        }
+
+        Style {
+            colour,
+            codes: if attributes.is_empty() { None } else { Some(attributes) },
+        }
+    }
+
+    fn insert(&mut self, key: &str, sequence: &str) {
+        let mut style: Style = Self::parse_sgr(sequence);
+
+        // Files always have a generated colour, which a catch-all like `fi=00;37` would replace
+        if key == "fi" {
+            style.colour = None;
+        }
+
+        if let Some(suffix) = key.strip_prefix('*') {
+            let suffix: String = suffix.to_lowercase();
+            self.suffixes.retain(|(existing, _)| existing != &suffix);
+            self.suffixes.push((suffix, style));
+        } else {
+            self.indicators.insert(key.to_string(), style);
+        }
+    }
+
+    fn sort_suffixes(&mut self) {
+        self.suffixes.sort_by_key(|(suffix, _)| Reverse(suffix.len()));
+    }
+
+    /// Parses the `key=value:key=value` format used by the `LS_COLORS` variable.
+    pub fn parse(spec: &str) -> Self {
+        let mut ls_colors: LsColors = LsColors::default();
+
+        for assignment in spec.split(':') {
+            if let Some((key, sequence)) = assignment.split_once('=') {
+                ls_colors.insert(key, sequence);
+            }
+        }
+
+        ls_colors.sort_suffixes();
+        ls_colors
+    }
+
+    /// Parses a `dircolors` database, as written by `dircolors --print-database`.
+    ///
+    /// `TERM` and `COLORTERM` sections are not evaluated, every entry of the file applies.
+    pub fn from_dircolors(contents: &str) -> Self {
+        let mut ls_colors: LsColors = LsColors::default();
+
+        for line in contents.lines() {
+            let line: &str = line.split('#').next().unwrap_or("").trim();
+            let mut words = line.split_whitespace();
+
+            let (keyword, sequence) = match (words.next(), words.next()) {
+                (Some(keyword), Some(sequence)) => (keyword, sequence),
+                _ => continue,
+            };
+
+            if keyword.starts_with('*') {
+                ls_colors.insert(keyword, sequence);
+            } else if let Some(extension) = keyword.strip_prefix('.') {
+                ls_colors.insert(&format!("*.{}", extension), sequence);
+            } else if let Some(indicator) = dircolors_indicator(keyword) {
+                ls_colors.insert(indicator, sequence);
+            }
+        }
+
+        ls_colors.sort_suffixes();
+        ls_colors
+    }
+
+    fn indicator(file_type: &FileType, mode: u32, path: &Path) -> &'static str {
+        if file_type.is_dir() {
+            match (mode & 0o1000 != 0, mode & 0o002 != 0) {
+                (true, true) => "tw",
+                (true, false) => "st",
+                (false, true) => "ow",
+                (false, false) => "di",
+            }
+        } else if file_type.is_symlink() {
+            if fs::metadata(path).is_err() {
+                "or"
+            } else {
+                "ln"
+            }
+        } else if file_type.is_fifo() {
+            "pi"
+        } else if file_type.is_socket() {
+            "so"
+        } else if file_type.is_block_device() {
+            "bd"
+        } else if file_type.is_char_device() {
+            "cd"
+        } else if mode & 0o4000 != 0 {
+            "su"
+        } else if mode & 0o2000 != 0 {
+            "sg"
+        } else if mode & 0o111 != 0 {
+            "ex"
+        } else {
+            "fi"
+        }
+    }
+
+    /// Looks up the style an entry should use, if the database has an opinion on it.
+    pub fn style_for(&self, lossy_name: &str, file_type: &FileType, mode: u32, path: &Path) -> Option<&Style> {
+        match Self::indicator(file_type, mode, path) {
+            "fi" => {
+                let lowercase_name: String = lossy_name.to_lowercase();
+
+                self.suffixes
+                    .iter()
+                    .find(|(suffix, _)| lowercase_name.ends_with(suffix.as_str()))
+                    .map(|(_, style)| style)
+                    .or_else(|| self.indicators.get("fi"))
+            }
+            "or" => self.indicators.get("or").or_else(|| self.indicators.get("ln")),
+            indicator => self.indicators.get(indicator),
+        }
+    }
+}
@@ -1,7 +1,27 @@
+mod collation;
 mod coloured_entry;
+mod colour_depth;
 mod config;
+mod git_status;
+mod icons;
+mod ls_colors;
+mod name_order;
+mod pattern;
 mod rgb_color;
+mod style;
+mod theme;
+mod visited;
 
+pub use collation::*;
 pub use coloured_entry::*;
+pub use colour_depth::*;
 pub use config::*;
+pub use git_status::*;
+pub use icons::*;
+pub use ls_colors::*;
+pub use name_order::*;
+pub use pattern::*;
 pub use rgb_color::*;
+pub use style::*;
+pub use theme::*;
+pub use visited::*;
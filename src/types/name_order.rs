@@ -0,0 +1,233 @@
+use std::cmp::Ordering;
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+
+/// How names compare with each other, for name sorting and to break ties between entries that
+/// any other sort field considers equal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameOrder {
+    /// Byte by byte, so `file10` comes before `file2`.
+    Bytes,
+    /// Runs of digits compare as numbers, so `file2` comes before `file10`.
+    Natural,
+    /// GNU version sort, like `ls -v`: runs of digits compare as numbers, letters come before
+    /// punctuation, `~` before anything and extensions only break ties, so `v1.9` comes before
+    /// `v1.10` and `1.0~rc1` before `1.0`.
+    Version,
+}
+
+impl NameOrder {
+    pub fn compare(&self, left: &OsStr, right: &OsStr) -> Ordering {
+        let (left, right): (&[u8], &[u8]) = (left.as_bytes(), right.as_bytes());
+
+        let ordering: Ordering = match self {
+            NameOrder::Bytes => Ordering::Equal,
+            NameOrder::Natural => natural_cmp(left, right),
+            NameOrder::Version => version_cmp(left, right),
+        };
+
+        ordering.then_with(|| left.cmp(right))
+    }
+}
+
+fn digit_run(bytes: &[u8]) -> usize {
+    bytes.iter().take_while(|byte| byte.is_ascii_digit()).count()
+}
+
+fn without_leading_zeros(digits: &[u8]) -> &[u8] {
+    let zeros: usize = digits.iter().take_while(|&&byte| byte == b'0').count();
+    &digits[zeros..]
+}
+
+// Numbers as long as they come, compared by length first since they have no leading zeros
+fn number_cmp(left: &[u8], right: &[u8]) -> Ordering {
+    let (left, right): (&[u8], &[u8]) = (without_leading_zeros(left), without_leading_zeros(right));
+    left.len().cmp(&right.len()).then_with(|| left.cmp(right))
+}
+
+fn natural_cmp(mut left: &[u8], mut right: &[u8]) -> Ordering {
+    loop {
+        match (left.first(), right.first()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(left_byte), Some(right_byte)) if left_byte.is_ascii_digit() && right_byte.is_ascii_digit() => {
+                let (left_len, right_len): (usize, usize) = (digit_run(left), digit_run(right));
+
+                let ordering: Ordering = number_cmp(&left[..left_len], &right[..right_len]);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+
+                left = &left[left_len..];
+                right = &right[right_len..];
+            }
+            (Some(left_byte), Some(right_byte)) => {
+                if left_byte != right_byte {
+                    return left_byte.cmp(right_byte);
+                }
+
+                left = &left[1..];
+                right = &right[1..];
+            }
+        }
+    }
+}
+
+// Weight of a byte outside of numbers in Debian's `verrevcmp`, where the end of a string and
+// digits weigh the same so that shorter versions come first
+fn version_weight(byte: Option<&u8>) -> i32 {
+    match byte {
+        None => 0,
+        Some(byte) if byte.is_ascii_digit() => 0,
+        Some(byte) if byte.is_ascii_alphabetic() => *byte as i32,
+        Some(b'~') => -1,
+        Some(byte) => *byte as i32 + 256,
+    }
+}
+
+fn verrevcmp(left: &[u8], right: &[u8]) -> Ordering {
+    let (mut i, mut j): (usize, usize) = (0, 0);
+
+    while i < left.len() || j < right.len() {
+        while left.get(i).is_some_and(|byte| !byte.is_ascii_digit())
+            || right.get(j).is_some_and(|byte| !byte.is_ascii_digit())
+        {
+            let (left_weight, right_weight): (i32, i32) = (version_weight(left.get(i)), version_weight(right.get(j)));
+            if left_weight != right_weight {
+                return left_weight.cmp(&right_weight);
+            }
+            i += 1;
+            j += 1;
+        }
+
+        let (left_rest, right_rest): (&[u8], &[u8]) = (&left[i..], &right[j..]);
+        let (left_len, right_len): (usize, usize) = (digit_run(left_rest), digit_run(right_rest));
+
+        let ordering: Ordering = number_cmp(&left_rest[..left_len], &right_rest[..right_len]);
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+
+        i += left_len;
+        j += right_len;
+    }
+
+    Ordering::Equal
+}
+
+// A name without its suffixes, as matched by `(\.[A-Za-z~][A-Za-z0-9~]*)*$`
+fn without_suffixes(name: &[u8]) -> &[u8] {
+    let mut end: usize = name.len();
+
+    // A leading dot makes a hidden file, not a suffix
+    while let Some(dot) = name[..end].iter().rposition(|&byte| byte == b'.').filter(|&dot| dot > 0) {
+        let suffix: &[u8] = &name[dot + 1..end];
+
+        let is_suffix: bool = suffix.first().is_some_and(|byte| byte.is_ascii_alphabetic() || *byte == b'~')
+            && suffix.iter().all(|byte| byte.is_ascii_alphanumeric() || *byte == b'~');
+        if !is_suffix {
+            break;
+        }
+
+        end = dot;
+    }
+
+    &name[..end]
+}
+
+// GNU `filevercmp`
+fn version_cmp(left: &[u8], right: &[u8]) -> Ordering {
+    // `.` and `..` come first, then hidden files
+    for special in [&b"."[..], &b".."[..]].iter() {
+        match (left == *special, right == *special) {
+            (true, true) => return Ordering::Equal,
+            (true, false) => return Ordering::Less,
+            (false, true) => return Ordering::Greater,
+            (false, false) => {}
+        }
+    }
+
+    let (left_hidden, right_hidden): (bool, bool) = (left.starts_with(b"."), right.starts_with(b"."));
+    if left_hidden != right_hidden {
+        return right_hidden.cmp(&left_hidden);
+    }
+
+    let (left, right): (&[u8], &[u8]) = if left_hidden { (&left[1..], &right[1..]) } else { (left, right) };
+
+    verrevcmp(without_suffixes(left), without_suffixes(right)).then_with(|| verrevcmp(left, right))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+    use std::ffi::OsStr;
+
+    use super::*;
+
+    fn compare(order: NameOrder, left: &str, right: &str) -> Ordering {
+        order.compare(OsStr::new(left), OsStr::new(right))
+    }
+
+    #[test]
+    fn bytes_compare_digits_as_text() {
+        assert_eq!(compare(NameOrder::Bytes, "file10", "file2"), Ordering::Less);
+    }
+
+    #[test]
+    fn natural_compares_numbers() {
+        assert_eq!(compare(NameOrder::Natural, "file2", "file10"), Ordering::Less);
+        assert_eq!(compare(NameOrder::Natural, "a9b", "a10a"), Ordering::Less);
+        assert_eq!(compare(NameOrder::Natural, "file", "file1"), Ordering::Less);
+    }
+
+    #[test]
+    fn leading_zeros_only_break_ties() {
+        assert_eq!(natural_cmp(b"file01", b"file1"), Ordering::Equal);
+        assert_eq!(compare(NameOrder::Natural, "file01", "file1"), Ordering::Less);
+        assert_eq!(compare(NameOrder::Natural, "file01", "file2"), Ordering::Less);
+        assert_eq!(compare(NameOrder::Version, "file01", "file1"), Ordering::Less);
+    }
+
+    #[test]
+    fn version_compares_numbers() {
+        assert_eq!(compare(NameOrder::Version, "file2", "file10"), Ordering::Less);
+        assert_eq!(compare(NameOrder::Version, "v1.9", "v1.10"), Ordering::Less);
+        assert_eq!(compare(NameOrder::Version, "1.2.10", "1.10.2"), Ordering::Less);
+    }
+
+    #[test]
+    fn tildes_come_before_anything() {
+        assert_eq!(compare(NameOrder::Version, "1.0~rc1", "1.0"), Ordering::Less);
+        assert_eq!(compare(NameOrder::Version, "1.0~rc1", "1.0~rc2"), Ordering::Less);
+    }
+
+    #[test]
+    fn letters_come_before_punctuation() {
+        assert_eq!(compare(NameOrder::Version, "1a", "1-"), Ordering::Less);
+        assert_eq!(compare(NameOrder::Version, "a_b", "ab"), Ordering::Greater);
+    }
+
+    #[test]
+    fn dot_entries_and_hidden_files_come_first() {
+        assert_eq!(compare(NameOrder::Version, ".", ".."), Ordering::Less);
+        assert_eq!(compare(NameOrder::Version, "..", ".hidden"), Ordering::Less);
+        assert_eq!(compare(NameOrder::Version, ".zshrc", "a"), Ordering::Less);
+        assert_eq!(compare(NameOrder::Version, ".b9", ".b10"), Ordering::Less);
+    }
+
+    #[test]
+    fn suffixes_are_stripped() {
+        assert_eq!(without_suffixes(b"foo.tar.gz"), b"foo");
+        assert_eq!(without_suffixes(b"foo-1.2.tar.gz"), b"foo-1.2");
+        assert_eq!(without_suffixes(b"a.1b"), b"a.1b");
+        assert_eq!(without_suffixes(b"a.b1"), b"a");
+        assert_eq!(without_suffixes(b".bashrc"), b".bashrc");
+    }
+
+    #[test]
+    fn suffixes_only_break_ties() {
+        assert_eq!(compare(NameOrder::Version, "foo-1.9.tar.gz", "foo-1.10.tar"), Ordering::Less);
+        assert_eq!(compare(NameOrder::Version, "foo.tar", "foo.tar.gz"), Ordering::Less);
+    }
+}
@@ -0,0 +1,192 @@
+use regex::{Regex, RegexBuilder};
+
+/// A filter given as a regex or as a shell-style glob.
+///
+/// Patterns containing a `/` match the entry's path, relative to the current directory like
+/// directory titles, the others only its name. Regexes match anywhere in the text, globs have
+/// to match all of it.
+#[derive(Debug)]
+pub struct Pattern {
+    matches_path: bool,
+    // Whether case matters is only known once every option has been read
+    case_sensitive: Regex,
+    case_insensitive: Regex,
+}
+
+// `*` and `?` stop at slashes, `**` doesn't and `**/` also matches no directory at all
+fn glob_to_regex(glob: &str) -> String {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut regex: String = String::from("^");
+    let mut index: usize = 0;
+
+    while index < chars.len() {
+        match chars[index] {
+            '*' if chars.get(index + 1) == Some(&'*') => {
+                if chars.get(index + 2) == Some(&'/') {
+                    regex.push_str("(?:.*/)?");
+                    index += 1;
+                } else {
+                    regex.push_str(".*");
+                }
+                index += 1;
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                let start: usize = index + 1;
+                let negated: bool = matches!(chars.get(start), Some('!') | Some('^'));
+                let first: usize = if negated { start + 1 } else { start };
+
+                // A `]` right after the opening bracket is part of the class
+                let closing: Option<usize> = chars
+                    .iter()
+                    .enumerate()
+                    .skip(first + 1)
+                    .find(|(_, &chr)| chr == ']')
+                    .map(|(closing, _)| closing);
+
+                match closing {
+                    Some(closing) => {
+                        regex.push_str(if negated { "[^" } else { "[" });
+                        for &chr in &chars[first..closing] {
+                            if chr == '-' {
+                                regex.push(chr);
+                            } else {
+                                regex.push_str(&regex::escape(&chr.to_string()));
+                            }
+                        }
+                        regex.push(']');
+                        index = closing;
+                    }
+                    None => regex.push_str(r"\["),
+                }
+            }
+            chr => regex.push_str(&regex::escape(&chr.to_string())),
+        }
+        index += 1;
+    }
+
+    regex.push('$');
+    regex
+}
+
+impl Pattern {
+    fn compile(source: &str, regex: &str) -> Result<Self, regex::Error> {
+        Ok(Pattern {
+            matches_path: source.contains('/'),
+            case_sensitive: Regex::new(regex)?,
+            case_insensitive: RegexBuilder::new(regex).case_insensitive(true).build()?,
+        })
+    }
+
+    pub fn regex(source: &str) -> Result<Self, regex::Error> {
+        Self::compile(source, source)
+    }
+
+    pub fn glob(source: &str) -> Result<Self, regex::Error> {
+        // Matched paths never start with `./`
+        let glob: &str = source.strip_prefix("./").unwrap_or(source);
+        Self::compile(source, &glob_to_regex(glob))
+    }
+
+    pub fn matches_path(&self) -> bool {
+        self.matches_path
+    }
+
+    pub fn is_match(&self, text: &str, ignore_case: bool) -> bool {
+        if ignore_case {
+            self.case_insensitive.is_match(text)
+        } else {
+            self.case_sensitive.is_match(text)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glob_matches(glob: &str, text: &str) -> bool {
+        Pattern::glob(glob).unwrap().is_match(text, false)
+    }
+
+    #[test]
+    fn stars_and_question_marks_stop_at_slashes() {
+        assert!(glob_matches("*.rs", "main.rs"));
+        assert!(!glob_matches("*.rs", "src/main.rs"));
+        assert!(glob_matches("file?", "file1"));
+        assert!(!glob_matches("file?", "file/"));
+        assert!(!glob_matches("file?", "file10"));
+    }
+
+    #[test]
+    fn double_stars_cross_slashes() {
+        assert!(glob_matches("src/**", "src/display/main.rs"));
+        assert!(glob_matches("**/*.rs", "main.rs"));
+        assert!(glob_matches("**/*.rs", "src/display/main.rs"));
+        assert!(glob_matches("src/**/mod.rs", "src/mod.rs"));
+        assert!(glob_matches("src/**/mod.rs", "src/types/mod.rs"));
+        assert!(!glob_matches("src/**/mod.rs", "srcmod.rs"));
+        assert_eq!(glob_to_regex("**/a"), "^(?:.*/)?a$");
+    }
+
+    #[test]
+    fn classes_match_one_character() {
+        assert!(glob_matches("file[0-9]", "file7"));
+        assert!(!glob_matches("file[0-9]", "filex"));
+        assert!(glob_matches("[!a]*", "bcd"));
+        assert!(!glob_matches("[!a]*", "abc"));
+        assert!(glob_matches("[^a]*", "bcd"));
+        assert!(!glob_matches("[^a]*", "abc"));
+    }
+
+    #[test]
+    fn a_closing_bracket_first_is_part_of_the_class() {
+        assert!(glob_matches("[]a]", "]"));
+        assert!(glob_matches("[]a]", "a"));
+        assert!(!glob_matches("[]a]", "b"));
+        assert!(glob_matches("[!]a]", "b"));
+        assert!(!glob_matches("[!]a]", "]"));
+    }
+
+    #[test]
+    fn unterminated_brackets_are_literal() {
+        assert!(glob_matches("[ab", "[ab"));
+        assert!(!glob_matches("[ab", "a"));
+        assert_eq!(glob_to_regex("x["), r"^x\[$");
+    }
+
+    #[test]
+    fn regex_syntax_is_escaped() {
+        assert!(glob_matches("a.b", "a.b"));
+        assert!(!glob_matches("a.b", "axb"));
+        assert!(glob_matches("(x)+{y}|z$", "(x)+{y}|z$"));
+        assert!(glob_matches("[.]rs", ".rs"));
+        assert!(!glob_matches("[.]rs", "xrs"));
+    }
+
+    #[test]
+    fn globs_match_whole_names_and_regexes_anywhere() {
+        assert!(!glob_matches("main", "main.rs"));
+        assert!(Pattern::regex("main").unwrap().is_match("src/main.rs", false));
+    }
+
+    #[test]
+    fn case_is_only_ignored_on_request() {
+        let pattern: Pattern = Pattern::glob("*.RS").unwrap();
+        assert!(!pattern.is_match("main.rs", false));
+        assert!(pattern.is_match("main.rs", true));
+    }
+
+    #[test]
+    fn patterns_with_a_slash_match_paths() {
+        assert!(Pattern::glob("src/*.rs").unwrap().matches_path());
+        assert!(!Pattern::glob("*.rs").unwrap().matches_path());
+        assert!(Pattern::regex(r"^src/").unwrap().matches_path());
+        assert!(!Pattern::regex(r"\.rs$").unwrap().matches_path());
+
+        let dotted: Pattern = Pattern::glob("./src/*").unwrap();
+        assert!(dotted.matches_path());
+        assert!(dotted.is_match("src/lib.rs", false));
+    }
+}
@@ -1,57 +1,199 @@
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct RgbColor {
     pub red: usize,
     pub green: usize,
     pub blue: usize,
 }
 
+// xterm's default palette for the 16 standard colours
+const ANSI_16: [(usize, usize, usize); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+const CUBE_LEVELS: [usize; 6] = [0, 95, 135, 175, 215, 255];
+
 impl RgbColor {
-    pub fn get_components_sum(&self) -> usize {
-        self.red + self.green + self.blue
+    /// Parses `#rrggbb` (the leading `#` is optional).
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let digits: &str = hex.strip_prefix('#').unwrap_or(hex);
+
+        if digits.len() != 6 || !digits.is_ascii() {
+            return None;
+        }
+
+        let component = |range| usize::from_str_radix(&digits[range], 16).ok();
+
+        Some(RgbColor {
+            red: component(0..2)?,
+            green: component(2..4)?,
+            blue: component(4..6)?,
+        })
     }
 
-    pub fn as_tuple(&self) -> (usize, usize, usize) {
-        (self.red, self.green, self.blue)
+    pub fn from_ansi16(index: u8) -> Self {
+        let (red, green, blue) = ANSI_16[index as usize % 16];
+        RgbColor { red, green, blue }
     }
 
-    pub fn pad_lowest(&mut self, min_rgb_sum: usize) {
-        let mut colors_sum: usize = self.get_components_sum();
+    pub fn from_ansi256(index: u8) -> Self {
+        match index {
+            0..=15 => Self::from_ansi16(index),
+            16..=231 => {
+                let cube_index: usize = index as usize - 16;
+                RgbColor {
+                    red: CUBE_LEVELS[cube_index / 36],
+                    green: CUBE_LEVELS[(cube_index / 6) % 6],
+                    blue: CUBE_LEVELS[cube_index % 6],
+                }
+            }
+            _ => {
+                let level: usize = 8 + (index as usize - 232) * 10;
+                RgbColor { red: level, green: level, blue: level }
+            }
+        }
+    }
+
+    fn distance(&self, other: &RgbColor) -> usize {
+        let square = |left: usize, right: usize| left.abs_diff(right).pow(2);
+        square(self.red, other.red) + square(self.green, other.green) + square(self.blue, other.blue)
+    }
+
+    /// Closest entry of the xterm 256-colour palette, ignoring the 16 user-configurable ones.
+    pub fn to_ansi256(&self) -> u8 {
+        let nearest_level = |component: usize| -> usize {
+            (0..CUBE_LEVELS.len())
+                .min_by_key(|&index| CUBE_LEVELS[index].abs_diff(component))
+                .unwrap_or(0)
+        };
+
+        let (red, green, blue) = (nearest_level(self.red), nearest_level(self.green), nearest_level(self.blue));
+        let cube_index: u8 = (16 + 36 * red + 6 * green + blue) as u8;
 
-        // Already good
-        if colors_sum > min_rgb_sum {
-            return;
+        let average: usize = (self.red + self.green + self.blue) / 3;
+        let grey_index: u8 = (232 + (average.saturating_sub(3) / 10).min(23)) as u8;
+
+        if Self::from_ansi256(grey_index).distance(self) < Self::from_ansi256(cube_index).distance(self) {
+            grey_index
+        } else {
+            cube_index
         }
+    }
+
+    /// Closest of the 16 standard colours, as found in xterm's default palette.
+    pub fn to_ansi16(&self) -> u8 {
+        (0..16u8)
+            .min_by_key(|&index| Self::from_ansi16(index).distance(self))
+            .unwrap_or(7)
+    }
+
+    pub fn to_hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+    }
 
-        let mut colors: [&mut usize; 3] = [&mut self.red, &mut self.green, &mut self.blue];
-        colors.sort_unstable();
+    pub fn as_tuple(&self) -> (usize, usize, usize) {
+        (self.red, self.green, self.blue)
+    }
 
-        let highest_addable_value: usize = 255 - *colors[2];
+    /// Converts an OKLCH colour (lightness 0-1, chroma, hue in degrees) to sRGB.
+    ///
+    /// Colours outside of the sRGB gamut keep their lightness and hue while their chroma is
+    /// reduced until they fit, so every result is as light as requested.
+    pub fn from_oklch(lightness: f64, chroma: f64, hue: f64) -> Self {
+        if let Some(rgb) = oklch_to_linear_srgb(lightness, chroma, hue) {
+            return Self::from_linear_srgb(rgb);
+        }
 
-        let diff: usize = min_rgb_sum - colors_sum;
+        let (mut low, mut high): (f64, f64) = (0.0, chroma);
 
-        // Just increment all 3 colors simultaneously
-        if (highest_addable_value * 3) > diff {
-            let to_add: usize = diff / 3;
-            for color in colors.iter_mut() {
-                **color = **color + to_add;
+        // Binary search on chroma, the achromatic colour is always inside the gamut
+        for _ in 0..24 {
+            let middle: f64 = (low + high) / 2.0;
+            if oklch_to_linear_srgb(lightness, middle, hue).is_some() {
+                low = middle;
+            } else {
+                high = middle;
             }
-            return;
         }
 
-        // Increment them by ascending color value
-        for color in colors.iter_mut() {
-            let potential_new_color: usize = **color + (min_rgb_sum - colors_sum);
+        let rgb: [f64; 3] = oklch_to_linear_srgb(lightness, low, hue).unwrap_or([lightness; 3]);
+        Self::from_linear_srgb(rgb)
+    }
 
-            if potential_new_color < 255 {
-                **color = potential_new_color;
-                return;
+    /// Converts to OKLCH, the inverse of `from_oklch`: lightness 0-1, chroma, hue in degrees
+    /// from 0 up to 360.
+    pub fn to_oklch(&self) -> (f64, f64, f64) {
+        let decode = |component: usize| -> f64 {
+            let gamma: f64 = component.min(255) as f64 / 255.0;
+            if gamma <= 0.040_45 {
+                gamma / 12.92
             } else {
-                let old_color: usize = **color;
+                ((gamma + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        let (r, g, b): (f64, f64, f64) = (decode(self.red), decode(self.green), decode(self.blue));
 
-                **color = 255;
+        let l: f64 = (0.412_221_470_8 * r + 0.536_332_536_3 * g + 0.051_445_992_9 * b).cbrt();
+        let m: f64 = (0.211_903_498_2 * r + 0.680_699_545_1 * g + 0.107_396_956_6 * b).cbrt();
+        let s: f64 = (0.088_302_461_9 * r + 0.281_718_837_6 * g + 0.629_978_700_5 * b).cbrt();
 
-                colors_sum += (255 - old_color) as usize;
-            }
-        }
+        let lightness: f64 = 0.210_454_255_3 * l + 0.793_617_785_0 * m - 0.004_072_046_8 * s;
+        let a: f64 = 1.977_998_495_1 * l - 2.428_592_205_0 * m + 0.450_593_709_9 * s;
+        let b: f64 = 0.025_904_037_1 * l + 0.782_771_766_2 * m - 0.808_675_766_0 * s;
+
+        (lightness, a.hypot(b), b.atan2(a).to_degrees().rem_euclid(360.0))
+    }
+
+    fn from_linear_srgb(rgb: [f64; 3]) -> Self {
+        let encode = |linear: f64| -> usize {
+            let linear: f64 = linear.clamp(0.0, 1.0);
+            let gamma: f64 = if linear <= 0.003_130_8 {
+                12.92 * linear
+            } else {
+                1.055 * linear.powf(1.0 / 2.4) - 0.055
+            };
+            (gamma * 255.0).round() as usize
+        };
+
+        RgbColor { red: encode(rgb[0]), green: encode(rgb[1]), blue: encode(rgb[2]) }
+    }
+}
+
+// Björn Ottosson's OKLab matrices, returns None when the colour falls outside of sRGB
+fn oklch_to_linear_srgb(lightness: f64, chroma: f64, hue: f64) -> Option<[f64; 3]> {
+    let (a, b): (f64, f64) = {
+        let radians: f64 = hue.to_radians();
+        (chroma * radians.cos(), chroma * radians.sin())
+    };
+
+    let l: f64 = (lightness + 0.396_337_777_4 * a + 0.215_803_757_3 * b).powi(3);
+    let m: f64 = (lightness - 0.105_561_345_8 * a - 0.063_854_172_8 * b).powi(3);
+    let s: f64 = (lightness - 0.089_484_177_5 * a - 1.291_485_548_0 * b).powi(3);
+
+    let rgb: [f64; 3] = [
+        4.076_741_662_1 * l - 3.307_711_591_3 * m + 0.230_969_929_2 * s,
+        -1.268_438_004_6 * l + 2.609_757_401_1 * m - 0.341_319_396_5 * s,
+        -0.004_196_086_3 * l - 0.703_418_614_7 * m + 1.707_614_701_0 * s,
+    ];
+
+    const EPSILON: f64 = 1e-6;
+    if rgb.iter().all(|component| (-EPSILON..=1.0 + EPSILON).contains(component)) {
+        Some(rgb)
+    } else {
+        None
     }
 }
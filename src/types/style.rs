@@ -0,0 +1,10 @@
+use crate::types::RgbColor;
+
+/// Explicit styling that takes over from the generated colour and the per-kind SGR digits.
+///
+/// Either half can be left unset, in which case the usual value is kept.
+#[derive(Debug, Clone, Default)]
+pub struct Style {
+    pub colour: Option<RgbColor>,
+    pub codes: Option<Vec<u8>>,
+}
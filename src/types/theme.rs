@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use toml::{Table, Value};
+
+use crate::types::{Kind, RgbColor, Style};
+
+const BUNDLED_THEMES: [(&str, &str); 3] = [
+    ("ferris", include_str!("../../themes/ferris.toml")),
+    ("pastel", include_str!("../../themes/pastel.toml")),
+    ("solarized", include_str!("../../themes/solarized.toml")),
+];
+
+/// Colours and SGR digits pinned by a theme file.
+///
+/// Exact filenames win over extensions, which win over kinds. Entries the theme doesn't list
+/// keep their generated colour.
+#[derive(Debug, Default)]
+pub struct Theme {
+    kinds: HashMap<Kind, Style>,
+    // Both keyed in lowercase
+    extensions: HashMap<String, Style>,
+    filenames: HashMap<String, Style>,
+}
+
+fn kind_from_name(name: &str) -> Option<Kind> {
+    let kind: Kind = match name {
+        "directory" | "directories" => Kind::Directory,
+        "sticky_directory" | "sticky_directories" => Kind::StickyDirectory,
+        "other_writable_directory" | "other_writable_directories" => Kind::OtherWritableDirectory,
+        "file" | "files" => Kind::File,
+        "executable" | "executables" => Kind::Executable,
+        "setuid" => Kind::Setuid,
+        "setgid" => Kind::Setgid,
+        "symlink" | "symlinks" => Kind::Symlink,
+        "broken_symlink" | "broken_symlinks" => Kind::BrokenSymlink,
+        "fifo" | "fifos" => Kind::Fifo,
+        "socket" | "sockets" => Kind::Socket,
+        "block_device" | "block_devices" => Kind::BlockDevice,
+        "char_device" | "char_devices" => Kind::CharDevice,
+        "unknown" | "unknowns" => Kind::Unknown,
+        _ => return None,
+    };
+    Some(kind)
+}
+
+fn parse_codes(key: &str, codes: &str) -> Result<Vec<u8>, String> {
+    codes
+        .chars()
+        .map(|chr| match chr.to_digit(10) {
+            Some(digit) => Ok(digit as u8),
+            None => Err(format!(r#"{}: Failed to convert "{}" to a digit."#, key, chr)),
+        })
+        .collect()
+}
+
+fn parse_style(key: &str, value: &Value) -> Result<Style, String> {
+    let table: &Table = match value {
+        Value::String(hex) => {
+            return match RgbColor::from_hex(hex) {
+                Some(colour) => Ok(Style { colour: Some(colour), codes: None }),
+                None => Err(format!(r#"{}: Failed to parse "{}" as a #rrggbb colour."#, key, hex)),
+            }
+        }
+        Value::Table(table) => table,
+        _ => return Err(format!("{}: Expected a colour string or a table.", key)),
+    };
+
+    let mut style: Style = Style::default();
+
+    for (field, value) in table {
+        let left: String = format!("{}.{}", key, field);
+
+        match (field.as_str(), value) {
+            ("colour", Value::String(hex)) | ("color", Value::String(hex)) => match RgbColor::from_hex(hex) {
+                Some(colour) => style.colour = Some(colour),
+                None => {
+                    return Err(format!(r#"{}: Failed to parse "{}" as a #rrggbb colour."#, left, hex))
+                }
+            },
+            ("style", Value::String(codes)) => style.codes = Some(parse_codes(&left, codes)?),
+            ("style", Value::Integer(codes)) => style.codes = Some(parse_codes(&left, &codes.to_string())?),
+            ("colour", _) | ("color", _) | ("style", _) => {
+                return Err(format!("{}: Expected a string.", left))
+            }
+            _ => return Err(format!("{}: Unknown key.", left)),
+        }
+    }
+
+    Ok(style)
+}
+
+fn parse_section(
+    name: &str,
+    value: &Value,
+    mut insert: impl FnMut(&str, Style) -> Result<(), String>,
+) -> Result<(), String> {
+    let table: &Table = match value {
+        Value::Table(table) => table,
+        _ => return Err(format!("{}: Expected a table.", name)),
+    };
+
+    for (key, value) in table {
+        let left: String = format!("{}.{}", name, key);
+        insert(key, parse_style(&left, value)?)?;
+    }
+
+    Ok(())
+}
+
+impl Theme {
+    /// Looks up a theme shipped with rainbow_ls.
+    pub fn bundled(name: &str) -> Option<&'static str> {
+        BUNDLED_THEMES
+            .iter()
+            .find(|(bundled_name, _)| *bundled_name == name)
+            .map(|(_, contents)| *contents)
+    }
+
+    pub fn bundled_names() -> Vec<&'static str> {
+        BUNDLED_THEMES.iter().map(|(name, _)| *name).collect()
+    }
+
+    /// Parses a theme made of `[kinds]`, `[extensions]` and `[filenames]` tables.
+    ///
+    /// Each entry is either a `"#rrggbb"` string or a table with optional `colour` and `style`
+    /// keys, `style` taking the same digits as `--files`. Errors name the offending key.
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let table: Table = contents.parse::<Table>().map_err(|error| error.to_string())?;
+        let mut theme: Theme = Theme::default();
+
+        for (section, value) in &table {
+            match section.as_str() {
+                "kinds" => parse_section(section, value, |key, style| {
+                    match kind_from_name(&key.to_lowercase()) {
+                        Some(kind) => {
+                            theme.kinds.insert(kind, style);
+                            Ok(())
+                        }
+                        None => Err(format!("{}.{}: Unknown kind.", section, key)),
+                    }
+                })?,
+                "extensions" => parse_section(section, value, |key, style| {
+                    let extension: &str = key.strip_prefix('.').unwrap_or(key);
+                    theme.extensions.insert(extension.to_lowercase(), style);
+                    Ok(())
+                })?,
+                "filenames" => parse_section(section, value, |key, style| {
+                    theme.filenames.insert(key.to_lowercase(), style);
+                    Ok(())
+                })?,
+                _ => return Err(format!("{}: Unknown section.", section)),
+            }
+        }
+
+        Ok(theme)
+    }
+
+    pub fn style_for(&self, lossy_name: &str, extension: Option<&str>, kind: &Kind) -> Option<&Style> {
+        self.filenames
+            .get(&lossy_name.to_lowercase())
+            .or_else(|| extension.and_then(|extension| self.extensions.get(&extension.to_lowercase())))
+            .or_else(|| self.kinds.get(kind))
+    }
+}
@@ -0,0 +1,47 @@
+use std::collections::HashSet;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use crate::error::{self, Error};
+
+/// Directories already listed, identified by device and inode so that every path leading to the
+/// same directory, through symlinks or otherwise, only counts once.
+#[derive(Debug, Default)]
+pub struct Visited {
+    ids: HashSet<(u64, u64)>,
+}
+
+fn directory_id(path: &Path) -> Option<(u64, u64)> {
+    fs::metadata(path).ok().map(|metadata| (metadata.dev(), metadata.ino()))
+}
+
+impl Visited {
+    /// Records `path`, returning `false` when its directory was already recorded.
+    ///
+    /// Paths that can't be stat'ed count as new, reading them reports the actual error.
+    pub fn insert(&mut self, path: &Path) -> bool {
+        match directory_id(path) {
+            Some(id) => self.ids.insert(id),
+            None => true,
+        }
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        if let Some(id) = directory_id(path) {
+            self.ids.remove(&id);
+        }
+    }
+
+    /// Whether `path` is a symlink itself, the only way a listing can lead back to a directory.
+    pub fn is_link(path: &Path) -> bool {
+        fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_symlink())
+    }
+
+    /// Tells the user that the link at `path` leads back to a directory that was already listed.
+    pub fn report(path: &Path) {
+        let target: PathBuf = fs::read_link(path).unwrap_or_else(|_| path.to_path_buf());
+
+        error::report(&Error::Loop { path: path.to_path_buf(), target });
+    }
+}